@@ -3,8 +3,6 @@ use tokio::net::TcpListener;
 use tracing::info;
 use tracing::level_filters::LevelFilter;
 
-use env;
-
 mod endpoints {
     pub(crate) mod health;
     pub(crate) mod schedule;
@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+use tracing::{debug, trace, warn};
+
+use crate::{
+    consts,
+    message::{self, Message, Update},
+    ClientConfig, Error, Subscription,
+};
+
+/// A client for the static timing archive f1 keeps of every past session.
+///
+/// The layout is a season `Index.json` listing meetings and their sessions,
+/// each session has its own `Index.json` listing the topics, recorded as
+/// a `.json` keyframe and a `.jsonStream` of timestamped updates.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    http: reqwest::Client,
+    base_url: Url,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Season {
+    pub year: i32,
+    pub meetings: Vec<Meeting>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Meeting {
+    pub key: i64,
+    pub name: String,
+    pub official_name: Option<String>,
+    pub location: Option<String>,
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Session {
+    pub key: i64,
+    #[serde(rename = "Type")]
+    pub kind: String,
+    pub name: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub gmt_offset: Option<String>,
+    /// relative to the archive, sessions that never happened have none
+    pub path: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SessionIndex {
+    /// keyed by the topic name, compressed topics keep their ".z"
+    pub feeds: BTreeMap<String, Feed>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Feed {
+    pub key_frame_path: Option<String>,
+    pub stream_path: Option<String>,
+}
+
+/// a single line of a `.jsonStream`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+    /// since the recording of the session started
+    pub offset: Duration,
+    pub data: Value,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Archive::new()
+    }
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Archive {
+            http: reqwest::Client::new(),
+            base_url: Url::parse(consts::F1_ARCHIVE_URL).expect("archive url is valid"),
+        }
+    }
+
+    /// uses another host than f1, for example a local copy of the archive
+    pub fn with_base_url(base_url: &str) -> Result<Self, Error> {
+        let mut base_url = base_url.to_owned();
+
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+
+        Ok(Archive {
+            http: reqwest::Client::new(),
+            base_url: Url::parse(&base_url).map_err(|e| Error::Protocol(e.to_string()))?,
+        })
+    }
+
+    /// goes through the proxy and trusts the roots of the config, like the hub connection
+    pub fn with_config(mut self, config: &ClientConfig) -> Result<Self, Error> {
+        self.http = config.http_client()?.build().map_err(Error::Archive)?;
+        Ok(self)
+    }
+
+    pub async fn season(&self, year: i32) -> Result<Season, Error> {
+        self.get_json(&format!("{}/Index.json", year)).await
+    }
+
+    pub async fn session_index(&self, path: &str) -> Result<SessionIndex, Error> {
+        self.get_json(&format!("{}Index.json", session_path(path)))
+            .await
+    }
+
+    /// the state of a topic at the end of the session
+    pub async fn keyframe(&self, path: &str, feed: &Feed) -> Result<Option<Value>, Error> {
+        let Some(key_frame_path) = &feed.key_frame_path else {
+            return Ok(None);
+        };
+
+        let url = format!("{}{}", session_path(path), key_frame_path);
+        Ok(Some(self.get_json(&url).await?))
+    }
+
+    pub async fn stream(&self, path: &str, feed: &Feed) -> Result<Vec<StreamEntry>, Error> {
+        let Some(stream_path) = &feed.stream_path else {
+            return Ok(Vec::new());
+        };
+
+        let body = self
+            .get_text(&format!("{}{}", session_path(path), stream_path))
+            .await?;
+
+        Ok(body.lines().filter_map(parse_stream_line).collect())
+    }
+
+    /// Downloads a session and converts it into the same messages the signalr hub sends.
+    ///
+    /// The first entry of every topic makes up the initial, all following entries
+    /// are sent as updates ordered by when they happened.
+    /// Topics without a stream are left out, their keyframe is the state at the end
+    /// of the session and would be wrong from the start of the replay on.
+    pub async fn messages(
+        &self,
+        path: &str,
+        subscription: &Subscription,
+    ) -> Result<Vec<Message>, Error> {
+        let index = self.session_index(path).await?;
+        let names = subscription.names();
+
+        let mut initial = Map::new();
+        let mut updates: Vec<(Duration, Update)> = Vec::new();
+        let mut start = None;
+
+        for (name, feed) in index.feeds.iter() {
+            if !names.contains(name) {
+                trace!("skipping archived topic {}", name);
+                continue;
+            }
+
+            let entries = match self.stream(path, feed).await {
+                Ok(entries) => entries,
+                // some topics only have a keyframe
+                Err(Error::ArchiveStatus(reqwest::StatusCode::NOT_FOUND)) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+
+            if name == "Heartbeat" {
+                start = entries.iter().find_map(recording_start);
+            }
+
+            let mut entries = entries.into_iter();
+
+            let Some(first) = entries.next() else {
+                warn!("archived topic {} has no stream, leaving it out", name);
+                continue;
+            };
+
+            let Some((topic, data)) = message::decode(name, first.data) else {
+                warn!("archived topic {} has no data", name);
+                continue;
+            };
+
+            initial.insert(topic, data);
+
+            for entry in entries {
+                let Some((topic, data)) = message::decode(name, entry.data) else {
+                    continue;
+                };
+
+                let update = Update {
+                    topic,
+                    data,
+                    timestamp: None,
+                };
+
+                updates.push((entry.offset, update));
+            }
+        }
+
+        updates.sort_by_key(|(offset, _)| *offset);
+
+        debug!(
+            "archived session {} has {} updates, recording started at {:?}",
+            path,
+            updates.len(),
+            start
+        );
+
+        let mut messages = vec![Message::Initial(Value::Object(initial))];
+
+        messages.extend(updates.into_iter().map(|(offset, mut update)| {
+            update.timestamp = start.map(|start| timestamp(start, offset));
+            Message::Updates(vec![update])
+        }));
+
+        Ok(messages)
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String, Error> {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| Error::Protocol(e.to_string()))?;
+
+        trace!("fetching archive url='{}'", url);
+
+        let res = self.http.get(url).send().await.map_err(Error::Archive)?;
+
+        if !res.status().is_success() {
+            return Err(Error::ArchiveStatus(res.status()));
+        }
+
+        let body = res.text().await.map_err(Error::Archive)?;
+
+        // the archive files start with a byte order mark
+        Ok(body.trim_start_matches('\u{feff}').to_owned())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let body = self.get_text(path).await?;
+        serde_json::from_str(&body).map_err(Error::InvalidArchive)
+    }
+}
+
+fn session_path(path: &str) -> String {
+    match path.ends_with('/') {
+        true => path.to_owned(),
+        false => format!("{}/", path),
+    }
+}
+
+// lines look like `00:01:02.345{"Status":"1"}`, the offset is always 12 characters
+fn parse_stream_line(line: &str) -> Option<StreamEntry> {
+    let line = line.trim_start_matches('\u{feff}').trim_end();
+    let (offset, data) = (line.get(..12)?, line.get(12..)?);
+
+    let mut parts = offset.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+
+    let offset = Duration::from_secs(hours * 3600 + minutes * 60)
+        + Duration::try_from_secs_f64(seconds).ok()?;
+
+    Some(StreamEntry {
+        offset,
+        data: serde_json::from_str(data).ok()?,
+    })
+}
+
+// the heartbeat carries the utc time, with its offset we know when the recording started
+fn recording_start(heartbeat: &StreamEntry) -> Option<DateTime<Utc>> {
+    let utc = heartbeat.data.get("Utc")?.as_str()?;
+    let utc = DateTime::parse_from_rfc3339(utc).ok()?.with_timezone(&Utc);
+
+    Some(utc - chrono::Duration::from_std(heartbeat.offset).ok()?)
+}
+
+fn timestamp(start: DateTime<Utc>, offset: Duration) -> String {
+    let time = start + chrono::Duration::from_std(offset).unwrap_or_default();
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
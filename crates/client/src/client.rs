@@ -1,141 +1,522 @@
-use std::{env, error::Error};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use axum::http::HeaderValue;
 
-use futures::SinkExt;
+use futures::{stream::BoxStream, SinkExt};
 use reqwest::{header, Url};
 use serde_json::Value;
 use tokio_stream::Stream;
 
+use tokio::time::timeout;
 use tokio_stream::StreamExt;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::{tungstenite::http::Request, MaybeTlsStream, WebSocketStream};
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 pub use tokio_tungstenite::tungstenite;
 
+pub mod archive;
+mod config;
 mod consts;
+mod error;
+pub mod feed;
+mod long_polling;
 pub mod message;
+mod subscription;
+
+pub use config::{ClientConfig, TlsBackend};
+pub use data::models::Topic;
+pub use error::Error;
+pub use subscription::Subscription;
 
 type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
 
-pub async fn parse_stream(stream: WsStream) -> impl Stream<Item = message::Message> {
-    stream.filter_map(|msg| msg.ok()).filter_map(|msg| {
-        match msg {
-            tungstenite::Message::Text(txt) => message::parse(txt),
-            tungstenite::Message::Close(_) => None, // how do i break out of the while let loop?
-            _ => None,
+/// The raw text frames of a connection, whatever transport they came over.
+pub type Frames = BoxStream<'static, Result<String, Error>>;
+
+pub async fn init(subscription: &Subscription) -> Result<Frames, Error> {
+    Connection::new(subscription.clone())
+        .with_transport(Transport::from_env())
+        .with_config(ClientConfig::from_env()?)
+        .connect()
+        .await
+}
+
+/// How we talk to the hub after negotiating.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// websockets, falling back to long polling when the hub does not offer them
+    /// or something between us and the hub breaks the handshake
+    #[default]
+    Auto,
+    WebSockets,
+    LongPolling,
+}
+
+impl Transport {
+    /// reads `SIGNALR_TRANSPORT`, one of "auto", "webSockets" or "longPolling"
+    pub fn from_env() -> Self {
+        match env::var("SIGNALR_TRANSPORT").as_deref() {
+            Ok("webSockets") => Transport::WebSockets,
+            Ok("longPolling") => Transport::LongPolling,
+            Ok("auto") | Err(_) => Transport::Auto,
+            Ok(other) => {
+                warn!("unknown transport '{}', using auto", other);
+                Transport::Auto
+            }
         }
-    })
+    }
+
+    /// the name signalr uses in the `transport` query parameter
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transport::Auto | Transport::WebSockets => "webSockets",
+            Transport::LongPolling => "longPolling",
+        }
+    }
+}
+
+/// A connection to the signalr hub that remembers where it left off,
+/// so a dropped socket can be resumed instead of starting over with a new initial.
+pub struct Connection {
+    subscription: Subscription,
+    hub_url: Url,
+    transport: Transport,
+    config: ClientConfig,
+    negotiation: Option<Negotiaion>,
+    /// the transport we ended up connecting with, reconnects use the same one
+    connected: Option<Transport>,
+    cursor: Arc<Mutex<message::Cursor>>,
 }
 
-pub async fn init() -> Result<WsStream, Box<dyn Error>> {
-    let req = create_request().await?;
+impl Connection {
+    pub fn new(subscription: Subscription) -> Self {
+        Connection {
+            subscription,
+            hub_url: Url::parse(consts::F1_HUB_URL).expect("hub url is valid"),
+            transport: Transport::default(),
+            config: ClientConfig::default(),
+            negotiation: None,
+            connected: None,
+            cursor: Arc::new(Mutex::new(message::Cursor::default())),
+        }
+    }
+
+    /// uses another hub than f1, for example a local stand-in
+    pub fn with_hub_url(mut self, hub_url: &str) -> Result<Self, Error> {
+        let mut hub_url = hub_url.to_owned();
+
+        if !hub_url.ends_with('/') {
+            hub_url.push('/');
+        }
+
+        self.hub_url = Url::parse(&hub_url).map_err(|e| Error::Protocol(e.to_string()))?;
+        Ok(self)
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// the proxy and tls settings used for every request to the hub
+    pub fn with_config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// negotiates a new connection and subscribes, we will receive a fresh initial
+    pub async fn connect(&mut self) -> Result<Frames, Error> {
+        self.reset();
+
+        if let Some(url) = env_url() {
+            trace!("connecting to '{}' directly", url);
+
+            // a url we can't make a request of won't get better by retrying
+            let req = url
+                .into_client_request()
+                .map_err(|e| Error::Protocol(e.to_string()))?;
+
+            return websocket(req, Some(self.subscription.message()), &self.config).await;
+        }
+
+        let negotiation = negotiate(&self.hub_url, &self.config).await?;
+
+        let transport = match self.transport {
+            Transport::Auto if !negotiation.try_websockets => {
+                debug!("hub does not offer websockets");
+                Transport::LongPolling
+            }
+            transport => transport,
+        };
+
+        let (connected, frames) = match transport {
+            Transport::LongPolling => (
+                Transport::LongPolling,
+                long_polling::connect(
+                    &self.hub_url,
+                    &negotiation,
+                    &self.subscription,
+                    &self.config,
+                )
+                .await?,
+            ),
+            _ => {
+                let req = create_request(&self.hub_url, "connect", &negotiation, &[])?;
+
+                match websocket(req, Some(self.subscription.message()), &self.config).await {
+                    Ok(frames) => (Transport::WebSockets, frames),
+                    Err(Error::Handshake(e)) if transport == Transport::Auto => {
+                        warn!(
+                            "websocket handshake failed, falling back to long polling: {}",
+                            e
+                        );
+
+                        let frames = long_polling::connect(
+                            &self.hub_url,
+                            &negotiation,
+                            &self.subscription,
+                            &self.config,
+                        )
+                        .await?;
+
+                        (Transport::LongPolling, frames)
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        debug!(
+            "subscribed to {:?} over {}",
+            self.subscription.names(),
+            connected.name()
+        );
+
+        self.negotiation = Some(negotiation);
+        self.connected = Some(connected);
+
+        Ok(frames)
+    }
+
+    /// resumes the connection after the last message we received,
+    /// the hub replays what we missed and we stay subscribed so no initial is sent
+    pub async fn reconnect(&mut self) -> Result<Frames, Error> {
+        let (Some(negotiation), Some(connected)) = (&self.negotiation, self.connected) else {
+            return Err(Error::NotResumable);
+        };
+
+        let cursor = self.cursor.lock().unwrap().clone();
+
+        let Some(message_id) = &cursor.message_id else {
+            return Err(Error::NotResumable);
+        };
+
+        let frames = match connected {
+            Transport::LongPolling => {
+                long_polling::reconnect(&self.hub_url, negotiation, &cursor, &self.config).await?
+            }
+            _ => {
+                let req = create_request(
+                    &self.hub_url,
+                    "reconnect",
+                    negotiation,
+                    &cursor_params(&cursor),
+                )?;
+
+                websocket(req, None, &self.config).await?
+            }
+        };
+
+        debug!("reconnected at message {}", message_id);
+
+        Ok(frames)
+    }
+
+    /// resumes if possible, otherwise falls back to a full connect
+    pub async fn resume_or_connect(&mut self) -> Result<Frames, Error> {
+        if !self.can_resume() {
+            return self.connect().await;
+        }
 
-    debug!("created request");
+        match self.reconnect().await {
+            Ok(frames) => Ok(frames),
+            Err(e) => {
+                debug!("failed to resume connection, connecting again: {}", e);
+                self.connect().await
+            }
+        }
+    }
 
+    pub fn can_resume(&self) -> bool {
+        self.negotiation.is_some() && !self.cursor.lock().unwrap().is_empty()
+    }
+
+    /// forgets the negotiated connection and cursor, the next connect starts fresh
+    pub fn reset(&mut self) {
+        self.negotiation = None;
+        self.connected = None;
+        *self.cursor.lock().unwrap() = message::Cursor::default();
+    }
+
+    pub fn cursor(&self) -> message::Cursor {
+        self.cursor.lock().unwrap().clone()
+    }
+
+    /// the transport of the current connection, none before we connected to a negotiated hub
+    pub fn transport(&self) -> Option<Transport> {
+        self.connected
+    }
+
+    /// how long we wait for any frame before we consider the connection dead,
+    /// over websockets the hub sends an empty `{}` keep-alive well within this window,
+    /// a long poll is held open for up to the connection timeout before it is answered
+    pub fn watchdog(&self) -> Option<Duration> {
+        let negotiation = self.negotiation.as_ref()?;
+
+        match self.connected? {
+            Transport::LongPolling => negotiation
+                .connection_timeout
+                .map(|timeout| timeout + long_polling::GRACE),
+            _ => negotiation
+                .keep_alive_timeout
+                .or(negotiation.disconnect_timeout),
+        }
+    }
+
+    /// parses the frames into messages, keeps track of the cursor for [`Connection::reconnect`]
+    /// and ends with an error when no frame arrived within [`Connection::watchdog`]
+    pub fn parse_stream(
+        &self,
+        frames: Frames,
+    ) -> impl Stream<Item = Result<message::Message, Error>> {
+        let cursor = self.cursor.clone();
+
+        watchdog(frames, self.watchdog()).filter_map(move |frame| match frame {
+            Ok(txt) => message::parse_with_cursor(txt, &mut cursor.lock().unwrap()).map(Ok),
+            Err(e) => Some(Err(e)),
+        })
+    }
+}
+
+async fn websocket(
+    req: Request<()>,
+    subscribe: Option<String>,
+    config: &ClientConfig,
+) -> Result<Frames, Error> {
     trace!("request='{:?}'", req);
 
-    let (mut socket, _) = tokio_tungstenite::connect_async(req).await?;
+    let url = Url::parse(&req.uri().to_string()).map_err(|e| Error::Protocol(e.to_string()))?;
+    let stream = config.tcp_stream(&url).await?;
+
+    let (mut socket, _) = tokio_tungstenite::client_async_tls_with_config(
+        req,
+        stream,
+        None,
+        Some(config.connector()?),
+    )
+    .await
+    .map_err(|e| Error::Handshake(Box::new(e)))?;
 
     debug!("connected");
 
-    socket
-        .send(tungstenite::Message::text(consts::SIGNALR_SUBSCRIBE))
-        .await?;
+    if let Some(subscribe) = subscribe {
+        socket
+            .send(tungstenite::Message::text(subscribe))
+            .await
+            .map_err(|e| Error::Subscribe(Box::new(e)))?;
+    }
 
-    debug!("subscribed");
+    Ok(Box::pin(websocket_frames(socket)))
+}
 
-    Ok(socket)
+fn websocket_frames(socket: WsStream) -> impl Stream<Item = Result<String, Error>> {
+    socket.filter_map(|msg| match msg {
+        Ok(tungstenite::Message::Text(txt)) => Some(Ok(txt)),
+        // pings are answered by tungstenite, a close ends the stream on its own
+        Ok(_) => None,
+        Err(e) => Some(Err(Error::Socket(Box::new(e)))),
+    })
 }
 
-async fn create_request() -> Result<Request<()>, Box<dyn Error>> {
-    trace!("creating request");
+fn watchdog(frames: Frames, window: Option<Duration>) -> impl Stream<Item = Result<String, Error>> {
+    futures::stream::unfold(Some(frames), move |frames| async move {
+        let mut frames = frames?;
+
+        let frame = match window {
+            Some(window) => match timeout(window, frames.next()).await {
+                Ok(frame) => frame,
+                Err(_) => {
+                    warn!("no frame received in {:?}, connection stalled", window);
+                    return Some((Err(Error::Timeout(window)), None));
+                }
+            },
+            None => frames.next().await,
+        };
+
+        frame.map(|frame| (frame, Some(frames)))
+    })
+}
 
-    match env_url() {
-        Some(url) => Ok(url.into_client_request()?),
-        None => {
-            trace!("no url detected");
-
-            let negotiation = negotiate().await?;
-
-            trace!(
-                "token='{}' cookie='{}'",
-                negotiation.token,
-                negotiation.cookie
-            );
-
-            let url = Url::parse_with_params(
-                &format!("wss://{}/connect", consts::F1_BASE_URL),
-                &[
-                    ("clientProtocol", "1.5"),
-                    ("transport", "webSockets"),
-                    ("connectionToken", &negotiation.token),
-                    ("connectionData", &consts::SIGNALR_HUB),
-                ],
-            )?;
-
-            trace!("url='{}'", url);
-
-            let mut req: Request<()> = url.into_client_request()?;
-
-            let headers = req.headers_mut();
-            headers.insert(
-                header::USER_AGENT, // asd
-                HeaderValue::from_static("BestHTTP"),
-            );
-            headers.insert(
-                header::ACCEPT_ENCODING,
-                HeaderValue::from_static("gzip,identity"),
-            );
-            headers.insert(
-                header::COOKIE, //asd
-                negotiation.cookie.parse().unwrap(),
-            );
-
-            Ok(req)
-        }
+/// the url of a hub endpoint with everything signalr wants to know about the connection
+fn endpoint_url(
+    hub_url: &Url,
+    endpoint: &str,
+    transport: Transport,
+    negotiation: &Negotiaion,
+    params: &[(&str, &str)],
+) -> Result<Url, Error> {
+    let mut url = hub_url
+        .join(endpoint)
+        .map_err(|e| Error::Protocol(e.to_string()))?;
+
+    url.query_pairs_mut()
+        .append_pair("clientProtocol", "1.5")
+        .append_pair("transport", transport.name())
+        .append_pair("connectionToken", &negotiation.token)
+        .append_pair("connectionData", consts::SIGNALR_HUB)
+        .extend_pairs(params);
+
+    Ok(url)
+}
+
+fn cursor_params(cursor: &message::Cursor) -> Vec<(&str, &str)> {
+    let mut params = Vec::new();
+
+    if let Some(message_id) = &cursor.message_id {
+        params.push(("messageId", message_id.as_str()));
     }
+
+    if let Some(groups_token) = &cursor.groups_token {
+        params.push(("groupsToken", groups_token.as_str()));
+    }
+
+    params
 }
 
+fn create_request(
+    hub_url: &Url,
+    endpoint: &str,
+    negotiation: &Negotiaion,
+    params: &[(&str, &str)],
+) -> Result<Request<()>, Error> {
+    trace!("creating request");
+
+    trace!(
+        "token='{}' cookie='{}'",
+        negotiation.token,
+        negotiation.cookie
+    );
+
+    let mut url = endpoint_url(
+        hub_url,
+        endpoint,
+        Transport::WebSockets,
+        negotiation,
+        params,
+    )?;
+
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        _ => "wss",
+    };
+
+    url.set_scheme(scheme)
+        .map_err(|_| Error::Protocol(format!("can't use {} for a websocket", url)))?;
+
+    trace!("url='{}'", url);
+
+    let mut req: Request<()> = url
+        .into_client_request()
+        .map_err(|e| Error::Protocol(e.to_string()))?;
+
+    let headers = req.headers_mut();
+    headers.insert(
+        header::USER_AGENT, // asd
+        HeaderValue::from_static("BestHTTP"),
+    );
+    headers.insert(
+        header::ACCEPT_ENCODING,
+        HeaderValue::from_static("gzip,identity"),
+    );
+    headers.insert(
+        header::COOKIE, //asd
+        negotiation
+            .cookie
+            .parse()
+            .map_err(|_| Error::Protocol("cookie is not a valid header".to_owned()))?,
+    );
+
+    Ok(req)
+}
+
+#[derive(Clone)]
 struct Negotiaion {
     token: String,
     cookie: String,
+    keep_alive_timeout: Option<Duration>,
+    disconnect_timeout: Option<Duration>,
+    connection_timeout: Option<Duration>,
+    try_websockets: bool,
 }
 
-async fn negotiate() -> Result<Negotiaion, Box<dyn Error>> {
+async fn negotiate(hub_url: &Url, config: &ClientConfig) -> Result<Negotiaion, Error> {
     trace!("negotiating");
 
-    let url = Url::parse_with_params(
-        &format!("https://{}/negotiate", consts::F1_BASE_URL),
-        &[
-            ("clientProtocol", "1.5"),
-            ("connectionData", &consts::SIGNALR_HUB),
-        ],
-    )?;
+    let mut url = hub_url
+        .join("negotiate")
+        .map_err(|e| Error::Protocol(e.to_string()))?;
+
+    url.query_pairs_mut()
+        .append_pair("clientProtocol", "1.5")
+        .append_pair("connectionData", consts::SIGNALR_HUB);
 
-    let res = reqwest::get(url).await?;
+    let http = config.http_client()?.build().map_err(Error::Negotiation)?;
+    let res = http.get(url).send().await.map_err(Error::Negotiation)?;
+
+    if !res.status().is_success() {
+        return Err(Error::NegotiationStatus(res.status()));
+    }
 
-    // TODO refactor this
-    let headers = res.headers().clone();
-    let body = res.text().await?;
-    let json = serde_json::from_str::<Value>(&body)?;
+    let cookie = res
+        .headers()
+        .get(header::SET_COOKIE)
+        .and_then(|cookie| cookie.to_str().ok())
+        .ok_or(Error::MissingCookie)?
+        .to_string();
+
+    let body = res.text().await.map_err(Error::Negotiation)?;
+    let json = serde_json::from_str::<Value>(&body).map_err(Error::InvalidNegotiation)?;
 
     trace!("negotiation response='{}'", json);
 
+    let token = json["ConnectionToken"]
+        .as_str()
+        .filter(|token| !token.is_empty())
+        .ok_or(Error::MissingToken)?
+        .to_string();
+
     Ok(Negotiaion {
-        token: json["ConnectionToken"]
-            .as_str()
-            .unwrap_or_default()
-            .to_string(),
-        cookie: headers[header::SET_COOKIE]
-            .to_str()
-            .unwrap_or_default()
-            .to_string(),
+        token,
+        cookie,
+        keep_alive_timeout: seconds(&json["KeepAliveTimeout"]),
+        disconnect_timeout: seconds(&json["DisconnectTimeout"]),
+        connection_timeout: seconds(&json["ConnectionTimeout"]),
+        try_websockets: json["TryWebSockets"].as_bool().unwrap_or(true),
     })
 }
 
+// timeouts that are zero, negative or too large for a duration count as not sent
+fn seconds(value: &Value) -> Option<Duration> {
+    value
+        .as_f64()
+        .filter(|secs| *secs > 0.0)
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+}
+
 fn env_url() -> Option<Url> {
     let env_url = env::var_os("WS_URL")?.into_string().ok()?;
-    Some(Url::parse(&env_url).ok()?)
+    Url::parse(&env_url).ok()
 }
@@ -0,0 +1,276 @@
+use std::{env, fs, io, path::PathBuf};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_tungstenite::Connector;
+use tracing::{debug, trace};
+
+use crate::{tungstenite, Error};
+
+/// Which tls implementation we connect to the hub with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsBackend {
+    /// the tls of the system, openssl on linux
+    #[default]
+    Native,
+    /// rustls with the mozilla roots, only available with the `rustls` feature
+    Rustls,
+}
+
+/// How we reach f1, for when we sit behind an egress proxy with its own ca.
+///
+/// Applies to the negotiate request, long polling and the websocket alike.
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// an http proxy all connections are tunneled through with `CONNECT`
+    pub proxy: Option<Url>,
+    /// pem encoded certificates trusted on top of the backend's roots
+    pub root_certificates: Vec<Vec<u8>>,
+    pub tls: TlsBackend,
+}
+
+impl ClientConfig {
+    /// reads `PROXY_URL`, `TLS_CA_CERTS` (comma separated pem files) and `TLS_BACKEND` ("native" or "rustls")
+    pub fn from_env() -> Result<Self, Error> {
+        let mut config = ClientConfig::default();
+
+        if let Ok(proxy) = env::var("PROXY_URL") {
+            config = config.proxy(&proxy)?;
+        }
+
+        if let Ok(paths) = env::var("TLS_CA_CERTS") {
+            for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                config = config.root_certificate_file(path)?;
+            }
+        }
+
+        match env::var("TLS_BACKEND").as_deref() {
+            Ok("rustls") => config.tls = TlsBackend::Rustls,
+            Ok("native") | Err(_) => {}
+            Ok(other) => return Err(Error::Config(format!("unknown tls backend '{}'", other))),
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn proxy(mut self, proxy: &str) -> Result<Self, Error> {
+        let proxy = Url::parse(proxy).map_err(|e| Error::Config(e.to_string()))?;
+
+        if proxy.scheme() != "http" {
+            return Err(Error::Config(format!(
+                "only http proxies are supported, got '{}'",
+                proxy.scheme()
+            )));
+        }
+
+        self.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    pub fn root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn root_certificate_file(self, path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let pem = fs::read(&path)
+            .map_err(|e| Error::Config(format!("can't read {}: {}", path.display(), e)))?;
+
+        Ok(self.root_certificate(pem))
+    }
+
+    pub fn tls(mut self, tls: TlsBackend) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.tls == TlsBackend::Rustls && !cfg!(feature = "rustls") {
+            return Err(no_rustls());
+        }
+
+        Ok(())
+    }
+
+    /// a http client for the negotiate and long polling requests
+    pub(crate) fn http_client(&self) -> Result<reqwest::ClientBuilder, Error> {
+        self.validate()?;
+
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy.clone()).map_err(|e| Error::Config(e.to_string()))?,
+            );
+        }
+
+        for pem in &self.root_certificates {
+            let certificate =
+                reqwest::Certificate::from_pem(pem).map_err(|e| Error::Config(e.to_string()))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        #[cfg(feature = "rustls")]
+        if self.tls == TlsBackend::Rustls {
+            builder = builder.use_rustls_tls();
+        }
+
+        Ok(builder)
+    }
+
+    /// the connector the websocket handshake does its tls with
+    pub(crate) fn connector(&self) -> Result<Connector, Error> {
+        self.validate()?;
+
+        match self.tls {
+            TlsBackend::Native => self.native_connector(),
+            #[cfg(feature = "rustls")]
+            TlsBackend::Rustls => self.rustls_connector(),
+            #[cfg(not(feature = "rustls"))]
+            TlsBackend::Rustls => Err(no_rustls()),
+        }
+    }
+
+    fn native_connector(&self) -> Result<Connector, Error> {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        for pem in &self.root_certificates {
+            let certificate =
+                native_tls::Certificate::from_pem(pem).map_err(|e| Error::Config(e.to_string()))?;
+            builder.add_root_certificate(certificate);
+        }
+
+        let connector = builder.build().map_err(|e| Error::Config(e.to_string()))?;
+
+        Ok(Connector::NativeTls(connector))
+    }
+
+    #[cfg(feature = "rustls")]
+    fn rustls_connector(&self) -> Result<Connector, Error> {
+        use rustls::pki_types::{pem::PemObject, CertificateDer};
+        use std::sync::Arc;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        for pem in &self.root_certificates {
+            for certificate in CertificateDer::pem_slice_iter(pem) {
+                let certificate = certificate.map_err(|e| Error::Config(e.to_string()))?;
+                roots
+                    .add(certificate)
+                    .map_err(|e| Error::Config(e.to_string()))?;
+            }
+        }
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let config = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| Error::Config(e.to_string()))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Connector::Rustls(Arc::new(config)))
+    }
+
+    /// a tcp connection to the host of the url, tunneled through the proxy if we have one
+    pub(crate) async fn tcp_stream(&self, url: &Url) -> Result<TcpStream, Error> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| Error::Protocol(format!("'{}' has no host", url)))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| Error::Protocol(format!("'{}' has no port", url)))?;
+
+        let Some(proxy) = &self.proxy else {
+            return TcpStream::connect((host, port)).await.map_err(handshake);
+        };
+
+        debug!("tunneling to {}:{} through {}", host, port, proxy);
+
+        let proxy_host = proxy
+            .host_str()
+            .ok_or_else(|| Error::Config(format!("proxy '{}' has no host", proxy)))?;
+        let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+
+        let mut stream = TcpStream::connect((proxy_host, proxy_port))
+            .await
+            .map_err(handshake)?;
+
+        let mut req = format!(
+            "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+            host = host,
+            port = port
+        );
+
+        if !proxy.username().is_empty() {
+            // the url keeps them percent-encoded, the header wants them as they are
+            let credentials = format!(
+                "{}:{}",
+                percent_decode_str(proxy.username()).decode_utf8_lossy(),
+                percent_decode_str(proxy.password().unwrap_or_default()).decode_utf8_lossy()
+            );
+
+            req.push_str(&format!(
+                "Proxy-Authorization: Basic {}\r\n",
+                BASE64_STANDARD.encode(credentials)
+            ));
+        }
+
+        req.push_str("\r\n");
+
+        stream.write_all(req.as_bytes()).await.map_err(handshake)?;
+
+        let res = read_head(&mut stream).await.map_err(handshake)?;
+
+        trace!("proxy response='{}'", res);
+
+        let status = res
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or_default();
+
+        if status != "200" {
+            return Err(handshake(io::Error::other(format!(
+                "proxy refused the tunnel: {}",
+                res.lines().next().unwrap_or_default()
+            ))));
+        }
+
+        Ok(stream)
+    }
+}
+
+// byte by byte so we don't read into what the hub sends once the tunnel is up
+async fn read_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8 * 1024 {
+            return Err(io::Error::other("proxy response head is too large"));
+        }
+
+        head.push(stream.read_u8().await?);
+    }
+
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn handshake(e: io::Error) -> Error {
+    Error::Handshake(Box::new(tungstenite::Error::Io(e)))
+}
+
+fn no_rustls() -> Error {
+    Error::Config(
+        "rustls was asked for but the client is built without the rustls feature".to_owned(),
+    )
+}
@@ -0,0 +1,108 @@
+use std::{fmt, time::Duration};
+
+use crate::tungstenite;
+
+#[derive(Debug)]
+pub enum Error {
+    /// the negotiate request could not be sent or its body not read
+    Negotiation(reqwest::Error),
+    /// the negotiate request was answered with a non success status
+    NegotiationStatus(reqwest::StatusCode),
+    /// the negotiate response is not the json we expect
+    InvalidNegotiation(serde_json::Error),
+    /// the negotiate response did not set the cookie we need to connect
+    MissingCookie,
+    /// the negotiate response did not contain a connection token
+    MissingToken,
+    /// connecting to the hub or the websocket handshake failed
+    Handshake(Box<tungstenite::Error>),
+    /// sending the subscribe to the hub failed
+    Subscribe(Box<tungstenite::Error>),
+    /// the websocket failed after we were connected
+    Socket(Box<tungstenite::Error>),
+    /// a long polling request could not be sent or its body not read
+    Poll(reqwest::Error),
+    /// the hub answered a long polling request with a non success status
+    PollStatus(reqwest::StatusCode),
+    /// no frame arrived within the keep-alive window
+    Timeout(Duration),
+    /// there is no negotiated connection or cursor to resume from
+    NotResumable,
+    /// a request to the static archive failed
+    Archive(reqwest::Error),
+    /// the archive answered with a non success status, most likely the file does not exist
+    ArchiveStatus(reqwest::StatusCode),
+    /// an archive index is not the json we expect
+    InvalidArchive(serde_json::Error),
+    /// reading a local recording failed
+    Io(std::io::Error),
+    /// the proxy or tls settings can't be used, like an unreadable certificate
+    Config(String),
+    /// anything that does not follow the signalr protocol, like an invalid url or header
+    Protocol(String),
+}
+
+impl Error {
+    /// true when retrying the same thing later can succeed, false when something has to change first
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Negotiation(_) | Error::Handshake(_) | Error::Subscribe(_) => true,
+            Error::Socket(_) | Error::Timeout(_) => true,
+            Error::Poll(_) => true,
+            Error::PollStatus(status) => status.is_server_error(),
+            Error::NegotiationStatus(status) => status.is_server_error(),
+            Error::Archive(_) => true,
+            Error::ArchiveStatus(status) => status.is_server_error(),
+            Error::InvalidArchive(_) => false,
+            Error::InvalidNegotiation(_) | Error::MissingCookie | Error::MissingToken => false,
+            Error::NotResumable | Error::Io(_) | Error::Protocol(_) => false,
+            Error::Config(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Negotiation(e) => write!(f, "negotiation failed: {}", e),
+            Error::NegotiationStatus(status) => {
+                write!(f, "negotiation failed with status {}", status)
+            }
+            Error::InvalidNegotiation(e) => write!(f, "invalid negotiation response: {}", e),
+            Error::MissingCookie => write!(f, "negotiation response is missing the cookie"),
+            Error::MissingToken => write!(f, "negotiation response is missing the token"),
+            Error::Handshake(e) => write!(f, "handshake failed: {}", e),
+            Error::Subscribe(e) => write!(f, "subscribing failed: {}", e),
+            Error::Socket(e) => write!(f, "socket failed: {}", e),
+            Error::Poll(e) => write!(f, "long polling request failed: {}", e),
+            Error::PollStatus(status) => {
+                write!(f, "long polling request failed with status {}", status)
+            }
+            Error::Timeout(window) => write!(f, "no frame received in {:?}", window),
+            Error::NotResumable => write!(f, "no connection to resume"),
+            Error::Archive(e) => write!(f, "archive request failed: {}", e),
+            Error::ArchiveStatus(status) => {
+                write!(f, "archive request failed with status {}", status)
+            }
+            Error::InvalidArchive(e) => write!(f, "invalid archive index: {}", e),
+            Error::Io(e) => write!(f, "reading the recording failed: {}", e),
+            Error::Config(e) => write!(f, "invalid client config: {}", e),
+            Error::Protocol(e) => write!(f, "protocol error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Negotiation(e) => Some(e),
+            Error::InvalidNegotiation(e) => Some(e),
+            Error::Poll(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Archive(e) => Some(e),
+            Error::InvalidArchive(e) => Some(e),
+            Error::Handshake(e) | Error::Subscribe(e) | Error::Socket(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    env,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader},
+    sync::{mpsc, Mutex},
+};
+use tokio_stream::wrappers::LinesStream;
+use tracing::info;
+
+use crate::{archive::Archive, message, ClientConfig, Connection, Error, Subscription, Transport};
+
+pub type FeedStream = BoxStream<'static, Result<message::Message, Error>>;
+
+/// Where live gets its messages from.
+///
+/// `open` is called again whenever the previous stream ended,
+/// `reset` when the consumer needs to start over, for example because the session changed.
+pub trait FeedSource: Send {
+    fn open(&mut self) -> BoxFuture<'_, Result<FeedStream, Error>>;
+
+    fn reset(&mut self) {}
+}
+
+/// Picks the source from the env, `FEED_FILE` replays a recording directly,
+/// `FEED_ARCHIVE` replays a session path from the f1 archive (or `ARCHIVE_URL`),
+/// otherwise we connect to the f1 signalr hub (or `WS_URL`) over `SIGNALR_TRANSPORT`.
+pub fn from_env() -> Result<Box<dyn FeedSource>, Error> {
+    let interval = env::var("FEED_INTERVAL_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_millis(100));
+
+    if let Some(path) = env::var_os("FEED_FILE") {
+        return Ok(Box::new(FileSource::new(path, interval)));
+    }
+
+    if let Ok(path) = env::var("FEED_ARCHIVE") {
+        let archive = match env::var("ARCHIVE_URL") {
+            Ok(url) => Archive::with_base_url(&url)?,
+            Err(_) => Archive::new(),
+        }
+        .with_config(&ClientConfig::from_env()?)?;
+
+        return Ok(Box::new(ArchiveSource::new(
+            archive,
+            path,
+            Subscription::from_env(),
+            interval,
+        )));
+    }
+
+    let connection = Connection::new(Subscription::from_env())
+        .with_transport(Transport::from_env())
+        .with_config(ClientConfig::from_env()?);
+
+    Ok(Box::new(SignalRSource::new(connection)))
+}
+
+/// The live f1 signalr hub, resumes dropped connections when possible.
+pub struct SignalRSource {
+    connection: Connection,
+}
+
+impl SignalRSource {
+    pub fn new(connection: Connection) -> Self {
+        SignalRSource { connection }
+    }
+}
+
+impl FeedSource for SignalRSource {
+    fn open(&mut self) -> BoxFuture<'_, Result<FeedStream, Error>> {
+        async move {
+            let frames = if self.connection.can_resume() {
+                info!("resuming client...");
+                self.connection.resume_or_connect().await?
+            } else {
+                info!("starting client...");
+                self.connection.connect().await?
+            };
+
+            Ok(self.connection.parse_stream(frames).boxed())
+        }
+        .boxed()
+    }
+
+    fn reset(&mut self) {
+        self.connection.reset();
+    }
+}
+
+/// A recording made by saver, one raw signalr frame per line.
+/// Like the simulator the feed stays open after the last line.
+///
+/// Opened again it goes on after the last line it fed, so a recording
+/// that spans a session change is replayed once and not up to the change over and over.
+pub struct FileSource {
+    path: PathBuf,
+    interval: Duration,
+    /// the lines fed so far
+    read: Arc<AtomicUsize>,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        FileSource {
+            path: path.into(),
+            interval,
+            read: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl FeedSource for FileSource {
+    fn open(&mut self) -> BoxFuture<'_, Result<FeedStream, Error>> {
+        async move {
+            let file = File::open(&self.path).await.map_err(Error::Io)?;
+            let skip = self.read.load(Ordering::Relaxed);
+
+            info!(
+                "replaying recording {} from line {}",
+                self.path.display(),
+                skip + 1
+            );
+
+            let lines = LinesStream::new(BufReader::new(file).lines())
+                .enumerate()
+                .skip(skip)
+                .filter_map(|(index, line)| async move { Some((index, line.ok()?)) })
+                .filter_map(|(index, line)| async move { Some((index, message::parse(line)?)) });
+
+            let lines = match self.interval.is_zero() {
+                true => lines.boxed(),
+                false => tokio_stream::StreamExt::throttle(lines, self.interval).boxed(),
+            };
+
+            // counted once handed out, what the throttle still holds is read again next time
+            let read = self.read.clone();
+            let lines = lines.map(move |(index, message)| {
+                read.store(index + 1, Ordering::Relaxed);
+                Ok(message)
+            });
+
+            Ok(lines.chain(futures::stream::pending()).boxed())
+        }
+        .boxed()
+    }
+}
+
+/// A past session from the f1 archive, downloaded once and replayed.
+/// Like the simulator the feed stays open after the last update.
+pub struct ArchiveSource {
+    archive: Archive,
+    path: String,
+    subscription: Subscription,
+    interval: Duration,
+    messages: Option<Vec<message::Message>>,
+}
+
+impl ArchiveSource {
+    pub fn new(
+        archive: Archive,
+        path: impl Into<String>,
+        subscription: Subscription,
+        interval: Duration,
+    ) -> Self {
+        ArchiveSource {
+            archive,
+            path: path.into(),
+            subscription,
+            interval,
+            messages: None,
+        }
+    }
+}
+
+impl FeedSource for ArchiveSource {
+    fn open(&mut self) -> BoxFuture<'_, Result<FeedStream, Error>> {
+        async move {
+            let messages = match &self.messages {
+                Some(messages) => messages.clone(),
+                None => {
+                    info!("downloading archived session {}", self.path);
+                    let messages = self
+                        .archive
+                        .messages(&self.path, &self.subscription)
+                        .await?;
+                    self.messages = Some(messages.clone());
+                    messages
+                }
+            };
+
+            let messages = futures::stream::iter(messages).map(Ok);
+
+            let messages = match self.interval.is_zero() {
+                true => messages.boxed(),
+                false => tokio_stream::StreamExt::throttle(messages, self.interval).boxed(),
+            };
+
+            Ok(messages.chain(futures::stream::pending()).boxed())
+        }
+        .boxed()
+    }
+}
+
+/// An in-memory source, mainly for tests, messages sent on the sender are fed as is.
+pub struct ChannelSource {
+    rx: Arc<Mutex<mpsc::Receiver<message::Message>>>,
+}
+
+pub fn channel(buffer: usize) -> (mpsc::Sender<message::Message>, ChannelSource) {
+    let (tx, rx) = mpsc::channel(buffer);
+
+    let source = ChannelSource {
+        rx: Arc::new(Mutex::new(rx)),
+    };
+
+    (tx, source)
+}
+
+impl FeedSource for ChannelSource {
+    fn open(&mut self) -> BoxFuture<'_, Result<FeedStream, Error>> {
+        let rx = self.rx.clone();
+
+        let stream = futures::stream::unfold(rx, |rx| async move {
+            let msg = rx.lock().await.recv().await?;
+            Some((Ok(msg), rx))
+        });
+
+        futures::future::ready(Ok(stream.boxed())).boxed()
+    }
+}
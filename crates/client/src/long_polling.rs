@@ -0,0 +1,206 @@
+use std::{collections::VecDeque, time::Duration};
+
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    Url,
+};
+use serde_json::Value;
+use tokio::time::sleep;
+use tracing::{debug, trace};
+
+use crate::{
+    cursor_params, endpoint_url, message::Cursor, ClientConfig, Error, Frames, Negotiaion,
+    Subscription, Transport,
+};
+
+/// on top of the connection timeout, the hub answers a poll that has nothing for us
+/// once the timeout is up, we give it this much longer before we call it stalled
+pub(crate) const GRACE: Duration = Duration::from_secs(10);
+
+/// The signalr long polling transport, for when websockets don't make it to the hub.
+///
+/// Every frame the hub would push over the socket is the answer to a `poll` instead,
+/// which we send again right away with the cursor of the last answer.
+struct LongPolling {
+    http: reqwest::Client,
+    hub_url: Url,
+    negotiation: Negotiaion,
+}
+
+/// connects, starts the connection and subscribes,
+/// the answers to all three are the first frames before we start polling
+pub(crate) async fn connect(
+    hub_url: &Url,
+    negotiation: &Negotiaion,
+    subscription: &Subscription,
+    config: &ClientConfig,
+) -> Result<Frames, Error> {
+    let client = LongPolling::new(hub_url, negotiation, config)?;
+
+    let mut cursor = Cursor::default();
+    let mut frames = VecDeque::new();
+
+    let init = client.get("connect", &[]).await?;
+    update_cursor(&mut cursor, &init);
+    frames.push_back(init);
+
+    // only tells us the connection is started, nothing the stream needs to see
+    client.get("start", &[]).await?;
+
+    let result = client.send(subscription.message()).await?;
+    update_cursor(&mut cursor, &result);
+    frames.push_back(result);
+
+    debug!("connected with long polling");
+
+    Ok(client.poll(cursor, frames))
+}
+
+/// picks the connection up at the cursor, the hub answers with what we missed
+pub(crate) async fn reconnect(
+    hub_url: &Url,
+    negotiation: &Negotiaion,
+    cursor: &Cursor,
+    config: &ClientConfig,
+) -> Result<Frames, Error> {
+    let client = LongPolling::new(hub_url, negotiation, config)?;
+
+    let mut cursor = cursor.clone();
+
+    let missed = client.get("reconnect", &cursor_params(&cursor)).await?;
+    update_cursor(&mut cursor, &missed);
+
+    Ok(client.poll(cursor, VecDeque::from([missed])))
+}
+
+impl LongPolling {
+    fn new(hub_url: &Url, negotiation: &Negotiaion, config: &ClientConfig) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, HeaderValue::from_static("BestHTTP"));
+        headers.insert(
+            header::COOKIE,
+            negotiation
+                .cookie
+                .parse()
+                .map_err(|_| Error::Protocol("cookie is not a valid header".to_owned()))?,
+        );
+
+        let http = config
+            .http_client()?
+            .default_headers(headers)
+            .build()
+            .map_err(Error::Poll)?;
+
+        Ok(LongPolling {
+            http,
+            hub_url: hub_url.clone(),
+            negotiation: negotiation.clone(),
+        })
+    }
+
+    fn url(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Url, Error> {
+        endpoint_url(
+            &self.hub_url,
+            endpoint,
+            Transport::LongPolling,
+            &self.negotiation,
+            params,
+        )
+    }
+
+    async fn get(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<String, Error> {
+        let url = self.url(endpoint, params)?;
+
+        trace!("url='{}'", url);
+
+        let res = self.http.get(url).send().await.map_err(Error::Poll)?;
+        text(res).await
+    }
+
+    /// invokes on the hub, the result comes back as the answer and not with the next poll
+    async fn send(&self, data: String) -> Result<String, Error> {
+        let url = self.url("send", &[])?;
+
+        trace!("url='{}' data='{}'", url, data);
+
+        let res = self
+            .http
+            .post(url)
+            .form(&[("data", data)])
+            .send()
+            .await
+            .map_err(Error::Poll)?;
+
+        text(res).await
+    }
+
+    fn poll(self, cursor: Cursor, pending: VecDeque<String>) -> Frames {
+        let poll = Poll {
+            client: self,
+            cursor,
+            pending,
+            delay: None,
+        };
+
+        Box::pin(futures::stream::unfold(Some(poll), |poll| async move {
+            let mut poll = poll?;
+
+            match poll.next().await {
+                Ok(frame) => Some((Ok(frame), Some(poll))),
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
+}
+
+struct Poll {
+    client: LongPolling,
+    cursor: Cursor,
+    /// frames we already have, handed out before we poll again
+    pending: VecDeque<String>,
+    /// the hub can ask us to wait before the next poll (`L`)
+    delay: Option<Duration>,
+}
+
+impl Poll {
+    async fn next(&mut self) -> Result<String, Error> {
+        if let Some(frame) = self.pending.pop_front() {
+            return Ok(frame);
+        }
+
+        if let Some(delay) = self.delay.take() {
+            sleep(delay).await;
+        }
+
+        let frame = self
+            .client
+            .get("poll", &cursor_params(&self.cursor))
+            .await?;
+
+        if let Ok(msg) = serde_json::from_str::<Value>(&frame) {
+            self.cursor.update(&msg);
+
+            self.delay = msg
+                .get("L")
+                .and_then(|l| l.as_u64())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis);
+        }
+
+        Ok(frame)
+    }
+}
+
+fn update_cursor(cursor: &mut Cursor, frame: &str) {
+    if let Ok(msg) = serde_json::from_str::<Value>(frame) {
+        cursor.update(&msg);
+    }
+}
+
+async fn text(res: reqwest::Response) -> Result<String, Error> {
+    if !res.status().is_success() {
+        return Err(Error::PollStatus(res.status()));
+    }
+
+    res.text().await.map_err(Error::Poll)
+}
@@ -1,42 +1,204 @@
-use std::mem;
-
+use data::compression;
 use serde_json::{Map, Value};
-use tracing::trace;
+use tracing::{error, trace};
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Message {
-    Updates(Vec<Map<String, Value>>),
+    Updates(Vec<Update>),
+    /// the result of our subscribe, the full state of every subscribed topic
     Initial(Value),
+    /// `S`, the transport is set up and ready to receive messages
+    Init,
+    /// `{}`, sent by the hub to show the connection is still alive
+    KeepAlive,
+    /// `E`, a hub invocation failed, for example the subscribe
+    Error {
+        id: Option<String>,
+        error: String,
+        /// `H`, the error was thrown by the hub and not the transport
+        hub_exception: bool,
+    },
+    /// `I` without `R`, an invocation finished without a result
+    Result {
+        id: String,
+    },
+    /// `T`, the hub asks us to reconnect
+    Reconnect,
+    /// `D`, the hub asks us to disconnect
+    Disconnect,
+    /// anything else the hub sends, like invocation progress
+    Unknown(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    /// the topic name, compressed topics are already inflated and without their ".z"
+    pub topic: String,
+    pub data: Value,
+    /// when f1 sent the update, as the utc iso string from the feed
+    pub timestamp: Option<String>,
+}
+
+impl Update {
+    /// the update as `{ topic: data }`, the shape the state is merged with
+    pub fn into_map(self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert(self.topic, self.data);
+        map
+    }
+}
+
+/// Where we are in the signalr stream, needed to resume a dropped connection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cursor {
+    /// the id of the last message we received (`C`)
+    pub message_id: Option<String>,
+    /// the token for the groups we are in, sent on init and when they change (`G`)
+    pub groups_token: Option<String>,
+}
+
+impl Cursor {
+    pub fn update(&mut self, msg: &Value) {
+        if let Some(id) = msg.get("C").and_then(|c| c.as_str()) {
+            self.message_id = Some(id.to_owned());
+        }
+
+        if let Some(token) = msg.get("G").and_then(|g| g.as_str()) {
+            self.groups_token = Some(token.to_owned());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.message_id.is_none()
+    }
 }
 
 pub fn parse(data: String) -> Option<Message> {
+    parse_with_cursor(data, &mut Cursor::default())
+}
+
+/// same as [`parse`] but also moves the cursor forward
+pub fn parse_with_cursor(data: String, cursor: &mut Cursor) -> Option<Message> {
     trace!("parsing message '{}'", data);
 
     let msg = serde_json::from_str::<Value>(&data).ok()?;
 
-    if let Some(initial) = msg.pointer("/R") {
-        return Some(Message::Initial(initial.clone()));
-    };
+    cursor.update(&msg);
 
-    if let Some(Value::Array(updates)) = msg.pointer("/M") {
-        if updates.len() < 1 {
-            return None;
-        }
+    // hub invocation responses, ours is the subscribe
+    if msg.get("I").is_some() {
+        return Some(parse_response(msg));
+    }
+
+    if is_set(&msg, "S") {
+        return Some(Message::Init);
+    }
 
-        let mut ups = Vec::new();
+    if is_set(&msg, "T") {
+        return Some(Message::Reconnect);
+    }
+
+    if is_set(&msg, "D") {
+        return Some(Message::Disconnect);
+    }
+
+    match msg.get("M") {
+        Some(Value::Array(updates)) if !updates.is_empty() => {
+            let mut ups = Vec::new();
+
+            for update in updates {
+                let Some(cat) = update.pointer("/A/0").and_then(|c| c.as_str()) else {
+                    continue;
+                };
+                let Some(data) = update.pointer("/A/1") else {
+                    continue;
+                };
+                let timestamp = update.pointer("/A/2").and_then(|t| t.as_str());
+
+                let Some((topic, data)) = decode(cat, data.clone()) else {
+                    continue;
+                };
 
-        for update in updates {
-            let cat = update.pointer("/A/0")?.as_str()?;
-            let data = update.pointer("/A/1")?;
+                ups.push(Update {
+                    topic,
+                    data,
+                    timestamp: timestamp.map(|t| t.to_owned()),
+                });
+            }
 
-            let mut up = Map::new();
-            up.insert(cat.to_owned(), data.clone());
-            ups.push(up);
+            Some(Message::Updates(ups))
         }
+        // only the cursor moved, it's a keep-alive for us
+        Some(Value::Array(_)) => Some(Message::KeepAlive),
+        Some(_) => Some(Message::Unknown(msg)),
+        None => match &msg {
+            Value::Object(map) if map.keys().all(|k| k == "C" || k == "G") => {
+                Some(Message::KeepAlive)
+            }
+            _ => Some(Message::Unknown(msg)),
+        },
+    }
+}
+
+fn parse_response(msg: Value) -> Message {
+    let id = match msg.get("I") {
+        Some(Value::String(id)) => Some(id.to_owned()),
+        Some(Value::Number(id)) => Some(id.to_string()),
+        _ => None,
+    };
 
-        return Some(Message::Updates(ups));
+    if let Some(error) = msg.get("E") {
+        return Message::Error {
+            id,
+            error: error
+                .as_str()
+                .map(|e| e.to_owned())
+                .unwrap_or(error.to_string()),
+            hub_exception: msg.get("H").and_then(|h| h.as_bool()).unwrap_or_default(),
+        };
     }
 
-    mem::drop(msg); // idk why I put this here
+    match msg.get("R") {
+        Some(Value::Object(initial)) => {
+            let mut decoded = Map::new();
+
+            for (cat, data) in initial {
+                if let Some((cat, data)) = decode(cat, data.clone()) {
+                    decoded.insert(cat, data);
+                }
+            }
 
-    None
+            Message::Initial(Value::Object(decoded))
+        }
+        Some(initial) => Message::Initial(initial.clone()),
+        None => match id {
+            // progress messages have an id like "P|1"
+            Some(id) if msg.get("P").is_none() => Message::Result { id },
+            _ => Message::Unknown(msg),
+        },
+    }
+}
+
+fn is_set(msg: &Value, key: &str) -> bool {
+    msg.get(key).and_then(|v| v.as_i64()) == Some(1)
+}
+
+// topics ending in ".z" (CarData.z, Position.z) are sent as base64 encoded raw deflate,
+// we inflate them here so everything after the client only sees plain json under "CarData" and "Position"
+pub(crate) fn decode(cat: &str, data: Value) -> Option<(String, Value)> {
+    let Some(name) = cat.strip_suffix(".z") else {
+        return Some((cat.to_owned(), data));
+    };
+
+    let Value::String(compressed) = data else {
+        return Some((name.to_owned(), data));
+    };
+
+    match compression::inflate(&compressed) {
+        Ok(inflated) => Some((name.to_owned(), inflated)),
+        Err(e) => {
+            error!("failed to inflate compressed topic {}: {}", cat, e);
+            None
+        }
+    }
 }
@@ -0,0 +1,121 @@
+use std::env;
+
+use data::models::Topic;
+use serde_json::json;
+
+/// The topics we subscribe to on the f1 hub.
+///
+/// Defaults to every topic we know of, use [`Subscription::from_env`] to configure it
+/// with `SUBSCRIBE_TOPICS`, `SUBSCRIBE_SKIP` and `SUBSCRIBE_EXTRA` (comma separated topic names).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    topics: Vec<Topic>,
+    extras: Vec<String>,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Subscription::all()
+    }
+}
+
+impl Subscription {
+    /// a subscription without any topics
+    pub fn new() -> Self {
+        Subscription {
+            topics: Vec::new(),
+            extras: Vec::new(),
+        }
+    }
+
+    pub fn all() -> Self {
+        Subscription::new().topics(Topic::ALL)
+    }
+
+    pub fn topic(mut self, topic: Topic) -> Self {
+        if !self.topics.contains(&topic) {
+            self.topics.push(topic);
+        }
+        self
+    }
+
+    pub fn topics(self, topics: impl IntoIterator<Item = Topic>) -> Self {
+        topics.into_iter().fold(self, |sub, topic| sub.topic(topic))
+    }
+
+    pub fn without(mut self, topic: Topic) -> Self {
+        self.topics.retain(|t| *t != topic);
+        self
+    }
+
+    /// subscribes to a topic we have no model for, the name is sent as is
+    pub fn extra(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.extras.contains(&name) {
+            self.extras.push(name);
+        }
+        self
+    }
+
+    pub fn from_env() -> Self {
+        let mut sub = match env_list("SUBSCRIBE_TOPICS") {
+            Some(names) => names.into_iter().fold(Subscription::new(), |sub, name| {
+                match Topic::from_name(&name) {
+                    Some(topic) => sub.topic(topic),
+                    None => sub.extra(name),
+                }
+            }),
+            None => Subscription::all(),
+        };
+
+        for name in env_list("SUBSCRIBE_SKIP").unwrap_or_default() {
+            match Topic::from_name(&name) {
+                Some(topic) => sub = sub.without(topic),
+                None => sub.extras.retain(|extra| *extra != name),
+            }
+        }
+
+        for name in env_list("SUBSCRIBE_EXTRA").unwrap_or_default() {
+            sub = sub.extra(name);
+        }
+
+        sub
+    }
+
+    pub fn contains(&self, topic: Topic) -> bool {
+        self.topics.contains(&topic)
+    }
+
+    /// the topic names as sent to f1, compressed topics get their ".z" suffix
+    pub fn names(&self) -> Vec<String> {
+        self.topics
+            .iter()
+            .map(|topic| match topic {
+                Topic::CarData | Topic::Position => format!("{}.z", topic.name()),
+                _ => topic.name().to_owned(),
+            })
+            .chain(self.extras.iter().cloned())
+            .collect()
+    }
+
+    pub(crate) fn message(&self) -> String {
+        json!({
+            "H": "Streaming",
+            "M": "Subscribe",
+            "A": [self.names()],
+            "I": 1,
+        })
+        .to_string()
+    }
+}
+
+fn env_list(key: &str) -> Option<Vec<String>> {
+    let list = env::var(key).ok()?;
+
+    Some(
+        list.split(',')
+            .map(|name| name.trim().to_owned())
+            .filter(|name| !name.is_empty())
+            .collect(),
+    )
+}
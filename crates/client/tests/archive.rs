@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use axum::{extract::Path, http::StatusCode, routing::get, Router};
+use client::{archive::Archive, message::Message, ClientConfig, Subscription, Topic};
+use serde_json::json;
+
+// serves the fixture tree like the static archive does
+async fn serve_fixtures() -> String {
+    let app = Router::new().route(
+        "/static/*path",
+        get(|Path(path): Path<String>| async move {
+            let file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/archive")
+                .join(path);
+
+            tokio::fs::read(file)
+                .await
+                .map_err(|_| StatusCode::NOT_FOUND)
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}/static", addr)
+}
+
+const SESSION: &str = "2024/2024-03-02_Bahrain_Grand_Prix/2024-03-02_Race/";
+
+#[tokio::test]
+async fn lists_season_sessions() {
+    let archive = Archive::with_base_url(&serve_fixtures().await).unwrap();
+
+    let season = archive.season(2024).await.unwrap();
+
+    assert_eq!(season.year, 2024);
+    assert_eq!(season.meetings[0].name, "Bahrain Grand Prix");
+    assert_eq!(
+        season.meetings[0].sessions[0].path.as_deref(),
+        Some(SESSION)
+    );
+
+    let index = archive.session_index(SESSION).await.unwrap();
+    assert!(index.feeds.contains_key("CarData.z"));
+
+    // still there for whoever wants the final state
+    let lap_count = archive
+        .keyframe(SESSION, &index.feeds["LapCount"])
+        .await
+        .unwrap();
+    assert_eq!(lap_count.unwrap()["CurrentLap"], 57);
+}
+
+#[tokio::test]
+async fn converts_session_to_messages() {
+    let archive = Archive::with_base_url(&serve_fixtures().await)
+        .unwrap()
+        .with_config(&ClientConfig::default())
+        .unwrap();
+
+    let messages = archive
+        .messages(SESSION, &Subscription::all())
+        .await
+        .unwrap();
+
+    let Message::Initial(initial) = &messages[0] else {
+        panic!("first message should be the initial");
+    };
+
+    assert_eq!(initial["SessionInfo"]["Name"], "Race");
+    assert_eq!(initial["TrackStatus"]["Status"], "1");
+    // only a keyframe, which is how the session ended and not how it started
+    assert!(initial.get("LapCount").is_none());
+    // compressed topics are inflated
+    assert_eq!(
+        initial["CarData"]["Entries"][0]["Cars"]["1"]["Channels"]["2"],
+        290
+    );
+
+    let updates: Vec<_> = messages[1..]
+        .iter()
+        .flat_map(|msg| match msg {
+            Message::Updates(updates) => updates.clone(),
+            _ => panic!("everything after the initial should be updates"),
+        })
+        .map(|u| (u.topic, u.timestamp.unwrap(), u.data))
+        .collect();
+
+    let expected = vec![
+        (
+            "TrackStatus",
+            "2024-03-02T14:00:05.500Z",
+            json!({"Status": "2", "Message": "Yellow"}),
+        ),
+        (
+            "CarData",
+            "2024-03-02T14:00:07.700Z",
+            json!({"Entries": [{"Utc": "2024-03-02T14:00:07.5Z", "Cars": {"1": {"Channels": {"0": 9000, "2": 150, "3": 3, "4": 0, "5": 100, "45": 0}}}}]}),
+        ),
+        (
+            "TrackStatus",
+            "2024-03-02T14:00:09.250Z",
+            json!({"Status": "1", "Message": "AllClear"}),
+        ),
+        (
+            "Heartbeat",
+            "2024-03-02T14:00:11.000Z",
+            json!({"Utc": "2024-03-02T14:00:11.000Z"}),
+        ),
+    ];
+
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(t, ts, d)| (t.to_owned(), ts.to_owned(), d))
+        .collect();
+
+    assert_eq!(updates, expected);
+}
+
+#[tokio::test]
+async fn only_converts_subscribed_topics() {
+    let archive = Archive::with_base_url(&serve_fixtures().await).unwrap();
+
+    let subscription = Subscription::new().topic(Topic::TrackStatus);
+    let messages = archive.messages(SESSION, &subscription).await.unwrap();
+
+    let Message::Initial(initial) = &messages[0] else {
+        panic!("first message should be the initial");
+    };
+
+    assert_eq!(initial.as_object().unwrap().len(), 1);
+    assert_eq!(messages.len(), 3);
+}
+
+#[tokio::test]
+async fn skips_offsets_that_are_no_duration() {
+    let archive = Archive::with_base_url(&serve_fixtures().await).unwrap();
+
+    let index = archive.session_index(SESSION).await.unwrap();
+    let entries = archive
+        .stream(SESSION, &index.feeds["TrackStatus"])
+        .await
+        .unwrap();
+
+    // a negative and an infinite offset are in the fixture, neither is a duration
+    let offsets: Vec<_> = entries.iter().map(|e| e.offset.as_millis()).collect();
+    assert_eq!(offsets, [0, 5500, 9250]);
+}
@@ -0,0 +1,52 @@
+use std::{io, time::Duration};
+
+use axum::http::StatusCode;
+use client::{tungstenite, Connection, Error, Subscription};
+
+#[test]
+fn connection_problems_are_transient() {
+    let transient = [
+        Error::Handshake(Box::new(tungstenite::Error::ConnectionClosed)),
+        Error::Subscribe(Box::new(tungstenite::Error::AlreadyClosed)),
+        Error::Socket(Box::new(tungstenite::Error::Io(io::Error::from(
+            io::ErrorKind::ConnectionReset,
+        )))),
+        Error::Timeout(Duration::from_secs(20)),
+        Error::NegotiationStatus(StatusCode::BAD_GATEWAY),
+        Error::PollStatus(StatusCode::SERVICE_UNAVAILABLE),
+        Error::ArchiveStatus(StatusCode::INTERNAL_SERVER_ERROR),
+    ];
+
+    for error in transient {
+        assert!(error.is_transient(), "{} should be transient", error);
+    }
+}
+
+#[test]
+fn configuration_problems_are_not() {
+    let permanent = [
+        Error::NegotiationStatus(StatusCode::FORBIDDEN),
+        Error::PollStatus(StatusCode::BAD_REQUEST),
+        Error::ArchiveStatus(StatusCode::NOT_FOUND),
+        Error::MissingCookie,
+        Error::MissingToken,
+        Error::NotResumable,
+        Error::Io(io::Error::from(io::ErrorKind::NotFound)),
+        Error::Config("TLS_CA_CERTS has no certificates".to_owned()),
+        Error::Protocol("relative URL without a base".to_owned()),
+    ];
+
+    for error in permanent {
+        assert!(!error.is_transient(), "{} should not be transient", error);
+    }
+}
+
+#[test]
+fn malformed_hub_url_is_not_transient() {
+    let Err(error) = Connection::new(Subscription::new()).with_hub_url("not a url") else {
+        panic!("the hub url should be rejected");
+    };
+
+    assert!(matches!(error, Error::Protocol(_)));
+    assert!(!error.is_transient());
+}
@@ -0,0 +1,76 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use client::{
+    feed::{FeedSource, FileSource},
+    message::Message,
+};
+use serde_json::json;
+use tokio_stream::StreamExt;
+
+// a saver recording of a race and the start of the session after it
+fn recording(name: &str) -> PathBuf {
+    let frames = [
+        json!({ "C": "d-1", "S": 1, "M": [] }),
+        json!({ "R": { "SessionInfo": { "Name": "Race" } }, "I": "1" }),
+        update("LapCount", json!({ "CurrentLap": 57 })),
+        update("SessionInfo", json!({ "Name": "Qualifying" })),
+        update("LapCount", json!({ "CurrentLap": 1 })),
+    ];
+
+    let lines: Vec<String> = frames.iter().map(|frame| frame.to_string()).collect();
+
+    let path = std::env::temp_dir().join(format!("feed-{}-{}.txt", name, std::process::id()));
+    fs::write(&path, lines.join("\n")).unwrap();
+    path
+}
+
+fn update(topic: &str, data: serde_json::Value) -> serde_json::Value {
+    json!({ "C": "d-2", "M": [{ "H": "Streaming", "M": "feed", "A": [topic, data, "2024-03-02T15:04:05.123Z"] }] })
+}
+
+fn topic(message: &Message) -> &str {
+    match message {
+        Message::Updates(updates) => &updates[0].topic,
+        _ => "",
+    }
+}
+
+#[tokio::test]
+async fn goes_on_after_a_session_change() {
+    let path = recording("session-change");
+    let mut source = FileSource::new(&path, Duration::ZERO);
+
+    // live stops reading at the change and opens the source again
+    let first: Vec<Message> = source
+        .open()
+        .await
+        .unwrap()
+        .take(4)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(first[0], Message::Init);
+    assert!(matches!(first[1], Message::Initial(_)));
+    assert_eq!(topic(&first[3]), "SessionInfo");
+
+    source.reset();
+
+    let mut stream = source.open().await.unwrap();
+    let next = stream.next().await.unwrap().unwrap();
+
+    let Message::Updates(updates) = next else {
+        panic!("the recording should go on with the next session");
+    };
+    assert_eq!(updates[0].topic, "LapCount");
+    assert_eq!(updates[0].data, json!({ "CurrentLap": 1 }));
+
+    // the end of the recording, the feed stays open without replaying it
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), stream.next())
+            .await
+            .is_err()
+    );
+
+    fs::remove_file(path).unwrap();
+}
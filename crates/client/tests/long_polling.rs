@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Form, Router,
+};
+use client::{message::Message, Connection, Subscription, Topic, Transport};
+use serde_json::json;
+use tokio_stream::StreamExt;
+
+type Params = Query<HashMap<String, String>>;
+
+/// what the stand-in hub was asked, in order
+#[derive(Clone, Default)]
+struct Hub {
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl Hub {
+    fn record(&self, endpoint: &str, params: &HashMap<String, String>) {
+        let message_id = params.get("messageId").cloned().unwrap_or_default();
+
+        self.requests.lock().unwrap().push(format!(
+            "{} {} {}",
+            endpoint,
+            params.get("transport").map(|t| t.as_str()).unwrap_or(""),
+            message_id
+        ));
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// a hub behind a proxy that kills websockets, only long polling makes it through
+async fn serve_hub() -> (String, Hub) {
+    let hub = Hub::default();
+
+    let app = Router::new()
+        .route(
+            "/signalr/negotiate",
+            get(|| async {
+                (
+                    [(header::SET_COOKIE, "GCLB=stand-in")],
+                    json!({
+                        "ConnectionToken": "token",
+                        "KeepAliveTimeout": 20.0,
+                        "DisconnectTimeout": 30.0,
+                        "ConnectionTimeout": 110.0,
+                        "TryWebSockets": true,
+                    })
+                    .to_string(),
+                )
+            }),
+        )
+        .route(
+            "/signalr/connect",
+            get(|State(hub): State<Hub>, Query(params): Params| async move {
+                hub.record("connect", &params);
+
+                match params["transport"].as_str() {
+                    "longPolling" => json!({ "C": "1", "S": 1, "M": [] })
+                        .to_string()
+                        .into_response(),
+                    _ => StatusCode::FORBIDDEN.into_response(),
+                }
+            }),
+        )
+        .route(
+            "/signalr/start",
+            get(|State(hub): State<Hub>, Query(params): Params| async move {
+                hub.record("start", &params);
+                json!({ "Response": "started" }).to_string()
+            }),
+        )
+        .route(
+            "/signalr/send",
+            post(
+                |State(hub): State<Hub>,
+                 Query(params): Params,
+                 Form(form): Form<HashMap<String, String>>| async move {
+                    hub.record("send", &params);
+
+                    assert!(form["data"].contains("Subscribe"));
+
+                    json!({ "R": { "TrackStatus": { "Status": "1" } }, "I": "1" }).to_string()
+                },
+            ),
+        )
+        .route(
+            "/signalr/poll",
+            get(|State(hub): State<Hub>, Query(params): Params| async move {
+                hub.record("poll", &params);
+
+                match params["messageId"].as_str() {
+                    "1" => update("2", "2"),
+                    id => json!({ "C": id, "M": [] }).to_string(),
+                }
+            }),
+        )
+        .route(
+            "/signalr/reconnect",
+            get(|State(hub): State<Hub>, Query(params): Params| async move {
+                hub.record("reconnect", &params);
+                update("3", "4")
+            }),
+        )
+        .with_state(hub.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (format!("http://{}/signalr", addr), hub)
+}
+
+fn update(message_id: &str, status: &str) -> String {
+    json!({
+        "C": message_id,
+        "M": [{
+            "H": "Streaming",
+            "M": "feed",
+            "A": ["TrackStatus", { "Status": status }, "2024-03-02T15:04:05.123Z"],
+        }],
+    })
+    .to_string()
+}
+
+fn connection(url: &str, transport: Transport) -> Connection {
+    Connection::new(Subscription::new().topic(Topic::TrackStatus))
+        .with_hub_url(url)
+        .unwrap()
+        .with_transport(transport)
+}
+
+#[tokio::test]
+async fn falls_back_to_long_polling() {
+    let (url, hub) = serve_hub().await;
+    let mut connection = connection(&url, Transport::Auto);
+
+    let frames = connection.connect().await.unwrap();
+
+    assert_eq!(connection.transport(), Some(Transport::LongPolling));
+
+    let messages: Vec<Message> = connection
+        .parse_stream(frames)
+        .take(4)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(messages[0], Message::Init);
+    assert_eq!(
+        messages[1],
+        Message::Initial(json!({ "TrackStatus": { "Status": "1" } }))
+    );
+
+    let Message::Updates(updates) = &messages[2] else {
+        panic!("the first poll should be answered with an update");
+    };
+    assert_eq!(updates[0].topic, "TrackStatus");
+    assert_eq!(updates[0].data, json!({ "Status": "2" }));
+    assert_eq!(
+        updates[0].timestamp.as_deref(),
+        Some("2024-03-02T15:04:05.123Z")
+    );
+
+    assert_eq!(messages[3], Message::KeepAlive);
+
+    assert_eq!(connection.cursor().message_id.as_deref(), Some("2"));
+
+    assert_eq!(
+        hub.requests(),
+        [
+            "connect webSockets ",
+            "connect longPolling ",
+            "start longPolling ",
+            "send longPolling ",
+            "poll longPolling 1",
+            "poll longPolling 2",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reconnects_long_polling_at_cursor() {
+    let (url, hub) = serve_hub().await;
+    let mut connection = connection(&url, Transport::LongPolling);
+
+    let frames = connection.connect().await.unwrap();
+
+    let messages: Vec<Message> = connection
+        .parse_stream(frames)
+        .take(3)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    assert!(matches!(messages[2], Message::Updates(_)));
+    assert!(connection.can_resume());
+
+    let frames = connection.resume_or_connect().await.unwrap();
+
+    let stream = connection.parse_stream(frames);
+    tokio::pin!(stream);
+
+    let message = stream.next().await.unwrap().unwrap();
+
+    let Message::Updates(updates) = message else {
+        panic!("the reconnect should be answered with what we missed");
+    };
+    assert_eq!(updates[0].data, json!({ "Status": "4" }));
+
+    assert_eq!(connection.cursor().message_id.as_deref(), Some("3"));
+
+    let requests = hub.requests();
+    assert_eq!(requests[0], "connect longPolling ");
+    assert!(requests.contains(&"reconnect longPolling 2".to_owned()));
+}
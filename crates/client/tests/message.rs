@@ -0,0 +1,171 @@
+use client::message::{parse, parse_with_cursor, Cursor, Message, Update};
+use serde_json::{json, Value};
+
+fn parsed(frame: Value) -> Message {
+    parse(frame.to_string()).unwrap()
+}
+
+#[test]
+fn init() {
+    assert_eq!(
+        parsed(json!({ "C": "d-1,0|Bj,0", "S": 1, "M": [] })),
+        Message::Init
+    );
+}
+
+#[test]
+fn keep_alive() {
+    assert_eq!(parsed(json!({})), Message::KeepAlive);
+    // a message id without any messages only moves the cursor
+    assert_eq!(parsed(json!({ "C": "d-2", "M": [] })), Message::KeepAlive);
+}
+
+#[test]
+fn updates() {
+    let message = parsed(json!({
+        "C": "d-3",
+        "M": [
+            { "H": "Streaming", "M": "feed", "A": ["TrackStatus", { "Status": "2" }, "2024-03-02T15:04:05.123Z"] },
+            { "H": "Streaming", "M": "feed", "A": ["LapCount", { "CurrentLap": 12 }] },
+            // not a feed message, skipped
+            { "H": "Streaming", "M": "feed", "A": [] }
+        ]
+    }));
+
+    assert_eq!(
+        message,
+        Message::Updates(vec![
+            Update {
+                topic: "TrackStatus".to_owned(),
+                data: json!({ "Status": "2" }),
+                timestamp: Some("2024-03-02T15:04:05.123Z".to_owned()),
+            },
+            Update {
+                topic: "LapCount".to_owned(),
+                data: json!({ "CurrentLap": 12 }),
+                timestamp: None,
+            },
+        ])
+    );
+}
+
+#[test]
+fn inflates_compressed_topics() {
+    // {"Position":[{"Timestamp":"2024-03-02T15:04:05.123Z"}]} as raw deflate
+    let compressed = "q1YKyC/OLMnMz1Oyiq5WCsnMTS0uScwtULJSMjIwMtE1MNY1MAoxNLUyMLEyMNUzNDKOUqqNrQUA";
+
+    let Message::Updates(updates) = parsed(json!({
+        "M": [{ "H": "Streaming", "M": "feed", "A": ["Position.z", compressed, "2024-03-02T15:04:05.200Z"] }]
+    })) else {
+        panic!("a compressed topic is still an update");
+    };
+
+    assert_eq!(updates[0].topic, "Position");
+    assert_eq!(
+        updates[0].data,
+        json!({ "Position": [{ "Timestamp": "2024-03-02T15:04:05.123Z" }] })
+    );
+
+    let Message::Initial(initial) = parsed(json!({ "R": { "Position.z": compressed }, "I": "1" }))
+    else {
+        panic!("the subscribe result is the initial");
+    };
+
+    assert!(initial.get("Position.z").is_none());
+    assert_eq!(
+        initial["Position"]["Position"][0]["Timestamp"],
+        "2024-03-02T15:04:05.123Z"
+    );
+}
+
+#[test]
+fn initial() {
+    assert_eq!(
+        parsed(json!({ "R": { "TrackStatus": { "Status": "1" } }, "I": "1" })),
+        Message::Initial(json!({ "TrackStatus": { "Status": "1" } }))
+    );
+}
+
+#[test]
+fn result_without_value() {
+    assert_eq!(
+        parsed(json!({ "I": "2" })),
+        Message::Result { id: "2".to_owned() }
+    );
+    assert_eq!(
+        parsed(json!({ "I": 3 })),
+        Message::Result { id: "3".to_owned() }
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        parsed(
+            json!({ "I": "1", "E": "There was an error invoking Hub method 'streaming.Subscribe'.", "H": true })
+        ),
+        Message::Error {
+            id: Some("1".to_owned()),
+            error: "There was an error invoking Hub method 'streaming.Subscribe'.".to_owned(),
+            hub_exception: true,
+        }
+    );
+
+    assert_eq!(
+        parsed(json!({ "I": "1", "E": { "Code": 500 } })),
+        Message::Error {
+            id: Some("1".to_owned()),
+            error: r#"{"Code":500}"#.to_owned(),
+            hub_exception: false,
+        }
+    );
+}
+
+#[test]
+fn reconnect_and_disconnect() {
+    assert_eq!(
+        parsed(json!({ "C": "d-4", "T": 1, "M": [] })),
+        Message::Reconnect
+    );
+    assert_eq!(parsed(json!({ "D": 1 })), Message::Disconnect);
+}
+
+#[test]
+fn unknown() {
+    let progress = json!({ "I": "P|1", "P": { "I": "1", "D": 50 } });
+    assert_eq!(parsed(progress.clone()), Message::Unknown(progress));
+
+    let other = json!({ "X": true });
+    assert_eq!(parsed(other.clone()), Message::Unknown(other));
+
+    assert_eq!(parse("not json".to_owned()), None);
+}
+
+#[test]
+fn moves_the_cursor() {
+    let mut cursor = Cursor::default();
+    assert!(cursor.is_empty());
+
+    parse_with_cursor(
+        json!({ "C": "d-1", "S": 1, "G": "groups-1", "M": [] }).to_string(),
+        &mut cursor,
+    );
+    assert_eq!(
+        cursor,
+        Cursor {
+            message_id: Some("d-1".to_owned()),
+            groups_token: Some("groups-1".to_owned()),
+        }
+    );
+
+    // the groups token is only sent when it changes
+    parse_with_cursor(json!({ "C": "d-2", "M": [] }).to_string(), &mut cursor);
+    assert_eq!(cursor.message_id.as_deref(), Some("d-2"));
+    assert_eq!(cursor.groups_token.as_deref(), Some("groups-1"));
+
+    // keep-alives and responses leave it alone
+    parse_with_cursor("{}".to_owned(), &mut cursor);
+    parse_with_cursor(json!({ "I": "1", "R": {} }).to_string(), &mut cursor);
+    assert_eq!(cursor.message_id.as_deref(), Some("d-2"));
+    assert!(!cursor.is_empty());
+}
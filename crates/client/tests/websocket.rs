@@ -0,0 +1,450 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    extract::{
+        ws::{Message as WsMessage, WebSocket},
+        Query, State, WebSocketUpgrade,
+    },
+    http::header,
+    response::Response,
+    routing::get,
+    Router,
+};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use client::{message::Message, ClientConfig, Connection, Error, Subscription, Topic, Transport};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::sleep,
+};
+use tokio_stream::StreamExt;
+
+type Params = Query<HashMap<String, String>>;
+
+/// what the stand-in hub was asked and sent, and how it behaves
+#[derive(Clone, Default)]
+struct Hub {
+    requests: Arc<Mutex<Vec<String>>>,
+    /// the text frames the client sent us
+    received: Arc<Mutex<Vec<String>>>,
+    keep_alive_timeout: Option<f64>,
+    disconnect_timeout: Option<f64>,
+    /// `{}` frames sent after the first update, 100ms apart
+    keep_alives: usize,
+    /// the hub goes quiet instead of closing the socket when it is done
+    stall: bool,
+}
+
+impl Hub {
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn serve_hub(hub: Hub) -> String {
+    let app = Router::new()
+        .route(
+            "/signalr/negotiate",
+            get(|State(hub): State<Hub>| async move {
+                (
+                    [(header::SET_COOKIE, "GCLB=stand-in")],
+                    json!({
+                        "ConnectionToken": "token",
+                        "KeepAliveTimeout": hub.keep_alive_timeout,
+                        "DisconnectTimeout": hub.disconnect_timeout,
+                        "ConnectionTimeout": 110.0,
+                        "TryWebSockets": true,
+                    })
+                    .to_string(),
+                )
+            }),
+        )
+        .route("/signalr/connect", get(connect))
+        .route("/signalr/reconnect", get(reconnect))
+        .with_state(hub.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}/signalr", addr)
+}
+
+async fn connect(ws: WebSocketUpgrade, State(hub): State<Hub>, Query(params): Params) -> Response {
+    hub.requests.lock().unwrap().push("connect".to_owned());
+    assert_eq!(params["transport"], "webSockets");
+
+    ws.on_upgrade(|mut socket| async move {
+        send(&mut socket, json!({ "C": "1", "S": 1, "M": [] })).await;
+
+        // nothing is sent before we were asked to subscribe
+        let Some(Ok(WsMessage::Text(subscribe))) = socket.recv().await else {
+            panic!("the client should subscribe first");
+        };
+        hub.received.lock().unwrap().push(subscribe);
+
+        send(
+            &mut socket,
+            json!({ "R": { "TrackStatus": { "Status": "1" } }, "I": "1" }),
+        )
+        .await;
+        send(&mut socket, update("2", "2")).await;
+
+        finish(socket, &hub).await;
+    })
+}
+
+async fn reconnect(
+    ws: WebSocketUpgrade,
+    State(hub): State<Hub>,
+    Query(params): Params,
+) -> Response {
+    hub.requests.lock().unwrap().push(format!(
+        "reconnect {} {}",
+        params["messageId"], params["groupsToken"]
+    ));
+
+    ws.on_upgrade(|mut socket| async move {
+        send(&mut socket, update("3", "4")).await;
+        finish(socket, &hub).await;
+    })
+}
+
+async fn finish(mut socket: WebSocket, hub: &Hub) {
+    for _ in 0..hub.keep_alives {
+        sleep(Duration::from_millis(100)).await;
+        send(&mut socket, json!({})).await;
+    }
+
+    if hub.stall {
+        // keep the socket open but say nothing, and note anything the client sends
+        while let Some(Ok(msg)) = socket.recv().await {
+            if let WsMessage::Text(txt) = msg {
+                hub.received.lock().unwrap().push(txt);
+            }
+        }
+    } else {
+        let _ = socket.send(WsMessage::Close(None)).await;
+    }
+}
+
+/// A stand-in http proxy that remembers the request heads it was sent.
+/// `CONNECT`s are tunneled as long as they carry credentials, plain requests are forwarded.
+async fn serve_proxy() -> (String, Arc<Mutex<Vec<String>>>) {
+    let heads = Arc::new(Mutex::new(Vec::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let recorded = heads.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let heads = recorded.clone();
+
+            tokio::spawn(async move {
+                let head = read_head(&mut client).await;
+                heads.lock().unwrap().push(head.clone());
+
+                let mut request_line = head.split_whitespace();
+                let method = request_line.next().unwrap();
+                let target = request_line.next().unwrap();
+
+                let mut upstream = match method {
+                    "CONNECT" if !head.contains("Proxy-Authorization: Basic ") => {
+                        let refused = "HTTP/1.1 407 Proxy Authentication Required\r\n\r\n";
+                        client.write_all(refused.as_bytes()).await.unwrap();
+                        return;
+                    }
+                    "CONNECT" => {
+                        let upstream = TcpStream::connect(target).await.unwrap();
+                        let established = "HTTP/1.1 200 Connection established\r\n\r\n";
+                        client.write_all(established.as_bytes()).await.unwrap();
+                        upstream
+                    }
+                    // "GET http://host:port/path HTTP/1.1"
+                    _ => {
+                        let host = target.trim_start_matches("http://").split('/').next();
+                        let mut upstream = TcpStream::connect(host.unwrap()).await.unwrap();
+                        upstream.write_all(head.as_bytes()).await.unwrap();
+                        upstream
+                    }
+                };
+
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+            });
+        }
+    });
+
+    (addr.to_string(), heads)
+}
+
+async fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await.unwrap());
+    }
+
+    String::from_utf8(head).unwrap()
+}
+
+async fn send(socket: &mut WebSocket, msg: Value) {
+    socket.send(WsMessage::Text(msg.to_string())).await.unwrap();
+}
+
+fn update(message_id: &str, status: &str) -> Value {
+    json!({
+        "C": message_id,
+        "G": format!("groups-{}", message_id),
+        "M": [{
+            "H": "Streaming",
+            "M": "feed",
+            "A": ["TrackStatus", { "Status": status }, "2024-03-02T15:04:05.123Z"],
+        }],
+    })
+}
+
+fn connection(url: &str) -> Connection {
+    Connection::new(Subscription::new().topic(Topic::TrackStatus))
+        .with_hub_url(url)
+        .unwrap()
+        .with_transport(Transport::WebSockets)
+}
+
+fn status(message: &Message) -> Option<Value> {
+    match message {
+        Message::Updates(updates) => Some(updates[0].data["Status"].clone()),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn resumes_websocket_at_cursor() {
+    let hub = Hub {
+        keep_alive_timeout: Some(20.0),
+        ..Hub::default()
+    };
+    let url = serve_hub(hub.clone()).await;
+    let mut connection = connection(&url);
+
+    assert!(!connection.can_resume());
+    assert!(matches!(
+        connection.reconnect().await,
+        Err(Error::NotResumable)
+    ));
+
+    let frames = connection.connect().await.unwrap();
+
+    assert_eq!(connection.transport(), Some(Transport::WebSockets));
+
+    // the hub closes the socket after the first update
+    let messages: Vec<Message> = connection
+        .parse_stream(frames)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0], Message::Init);
+    assert!(matches!(messages[1], Message::Initial(_)));
+    assert_eq!(status(&messages[2]), Some(json!("2")));
+
+    assert!(connection.can_resume());
+    assert_eq!(connection.cursor().message_id.as_deref(), Some("2"));
+    assert_eq!(
+        connection.cursor().groups_token.as_deref(),
+        Some("groups-2")
+    );
+
+    let frames = connection.resume_or_connect().await.unwrap();
+
+    let messages: Vec<Message> = connection
+        .parse_stream(frames)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    // only what we missed, no init and no new initial
+    assert_eq!(messages.len(), 1);
+    assert_eq!(status(&messages[0]), Some(json!("4")));
+    assert_eq!(connection.cursor().message_id.as_deref(), Some("3"));
+
+    assert_eq!(hub.requests(), ["connect", "reconnect 2 groups-2"]);
+
+    // the subscription carries over, it is not sent again
+    let received = hub.received();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].contains("Subscribe"));
+}
+
+#[tokio::test]
+async fn connects_again_after_reset() {
+    let url = serve_hub(Hub::default()).await;
+    let mut connection = connection(&url);
+
+    let frames = connection.connect().await.unwrap();
+    let _: Vec<_> = connection.parse_stream(frames).collect().await;
+
+    connection.reset();
+    assert!(!connection.can_resume());
+
+    let frames = connection.resume_or_connect().await.unwrap();
+    let messages: Vec<_> = connection.parse_stream(frames).collect().await;
+
+    assert!(matches!(messages[1], Ok(Message::Initial(_))));
+    assert_eq!(connection.transport(), Some(Transport::WebSockets));
+}
+
+#[tokio::test]
+async fn ends_when_keep_alives_stop() {
+    let hub = Hub {
+        keep_alive_timeout: Some(0.3),
+        disconnect_timeout: Some(30.0),
+        keep_alives: 4,
+        stall: true,
+        ..Hub::default()
+    };
+    let url = serve_hub(hub).await;
+    let mut connection = connection(&url);
+
+    let frames = connection.connect().await.unwrap();
+
+    assert_eq!(connection.watchdog(), Some(Duration::from_millis(300)));
+
+    let messages: Vec<_> = connection.parse_stream(frames).collect().await;
+
+    // the keep-alives keep the connection going well past the window
+    let keep_alives = messages
+        .iter()
+        .filter(|message| matches!(message, Ok(Message::KeepAlive)))
+        .count();
+    assert_eq!(keep_alives, 4);
+
+    let Some(Err(Error::Timeout(window))) = messages.last() else {
+        panic!("a stalled hub should end the stream with a timeout");
+    };
+    assert_eq!(*window, Duration::from_millis(300));
+    assert!(messages
+        .last()
+        .unwrap()
+        .as_ref()
+        .unwrap_err()
+        .is_transient());
+}
+
+#[tokio::test]
+async fn watchdog_falls_back_to_disconnect_timeout() {
+    let hub = Hub {
+        keep_alive_timeout: None,
+        disconnect_timeout: Some(0.2),
+        stall: true,
+        ..Hub::default()
+    };
+    let url = serve_hub(hub).await;
+    let mut connection = connection(&url);
+
+    assert_eq!(connection.watchdog(), None);
+
+    let frames = connection.connect().await.unwrap();
+
+    assert_eq!(connection.watchdog(), Some(Duration::from_millis(200)));
+
+    let messages: Vec<_> = connection.parse_stream(frames).collect().await;
+
+    assert_eq!(messages.len(), 4);
+    assert!(matches!(
+        messages.last(),
+        Some(Err(Error::Timeout(window))) if *window == Duration::from_millis(200)
+    ));
+}
+
+#[tokio::test]
+async fn ignores_timeouts_too_large_for_a_duration() {
+    let hub = Hub {
+        keep_alive_timeout: Some(1e300),
+        disconnect_timeout: Some(0.2),
+        ..Hub::default()
+    };
+    let url = serve_hub(hub).await;
+    let mut connection = connection(&url);
+
+    let _frames = connection.connect().await.unwrap();
+
+    assert_eq!(connection.watchdog(), Some(Duration::from_millis(200)));
+}
+
+#[tokio::test]
+async fn tunnels_through_an_authenticating_proxy() {
+    let url = serve_hub(Hub::default()).await;
+    let (proxy, heads) = serve_proxy().await;
+
+    // credentials with characters that have to be percent-encoded in the url
+    let config = ClientConfig::default()
+        .proxy(&format!("http://user%40corp:p%3Ass%25word@{}", proxy))
+        .unwrap();
+
+    let mut connection = connection(&url).with_config(config);
+
+    let frames = connection.connect().await.unwrap();
+
+    let messages: Vec<Message> = connection
+        .parse_stream(frames)
+        .map(|message| message.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0], Message::Init);
+
+    let heads = heads.lock().unwrap().clone();
+    let hub = url
+        .trim_start_matches("http://")
+        .trim_end_matches("/signalr");
+
+    // the negotiate is a plain request through the proxy
+    assert!(heads
+        .iter()
+        .any(|head| head.starts_with(&format!("GET http://{}/signalr/negotiate", hub))));
+
+    let tunnel = heads
+        .iter()
+        .find(|head| head.starts_with("CONNECT"))
+        .expect("the websocket should be tunneled");
+
+    assert!(tunnel.starts_with(&format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n", hub, hub)));
+    assert!(tunnel.contains(&format!(
+        "Proxy-Authorization: Basic {}\r\n",
+        BASE64_STANDARD.encode("user@corp:p:ss%word")
+    )));
+}
+
+#[tokio::test]
+async fn fails_when_the_proxy_refuses_the_tunnel() {
+    let url = serve_hub(Hub::default()).await;
+    let (proxy, _) = serve_proxy().await;
+
+    let config = ClientConfig::default()
+        .proxy(&format!("http://{}", proxy))
+        .unwrap();
+
+    let mut connection = connection(&url).with_config(config);
+
+    let Err(error) = connection.connect().await else {
+        panic!("the tunnel should be refused");
+    };
+
+    assert!(matches!(error, Error::Handshake(_)));
+    assert!(error.to_string().contains("407"));
+}
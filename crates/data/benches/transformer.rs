@@ -0,0 +1,170 @@
+use std::{fs, mem};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use data::{
+    compression,
+    transformer::{Casing, Transformer},
+};
+use serde_json::{Map, Value};
+
+/// generated in the saver format, not captured from f1, set `BENCH_RECORDING` for real numbers
+const RECORDING: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/benches/fixtures/recording.txt"
+);
+
+/// the initial state and every update of a saver recording
+struct Recording {
+    initial: Value,
+    updates: Vec<Map<String, Value>>,
+}
+
+fn recording() -> Recording {
+    let path = std::env::var("BENCH_RECORDING").unwrap_or_else(|_| RECORDING.to_owned());
+    let text = fs::read_to_string(&path).expect("failed to read recording");
+
+    let mut initial = Value::Null;
+    let mut updates = Vec::new();
+
+    for line in text.lines() {
+        let Ok(frame) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+
+        if let Some(result) = frame.get("R") {
+            initial = result.clone();
+        }
+
+        let Some(messages) = frame.get("M").and_then(|m| m.as_array()) else {
+            continue;
+        };
+
+        for message in messages {
+            let Some([Value::String(topic), data, ..]) = message
+                .get("A")
+                .and_then(|a| a.as_array())
+                .map(|a| a.as_slice())
+            else {
+                continue;
+            };
+
+            // what the client hands to live, compressed topics inflated
+            let (topic, data) = match (topic.strip_suffix(".z"), data) {
+                (Some(topic), Value::String(compressed)) => (
+                    topic.to_owned(),
+                    compression::inflate(compressed).expect("invalid compressed topic"),
+                ),
+                _ => (topic.to_owned(), data.clone()),
+            };
+
+            let mut update = Map::new();
+            update.insert(topic, data);
+            updates.push(update);
+        }
+    }
+
+    Recording { initial, updates }
+}
+
+// the transformer as it was, a new map and a heck conversion for every key
+fn allocating_transform(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let mut renamed = Map::new();
+
+            for (key, mut value) in mem::take(object) {
+                if key == "_kf" {
+                    continue;
+                }
+
+                allocating_transform(&mut value);
+                renamed.insert(heck::AsLowerCamelCase(&key).to_string(), value);
+            }
+
+            *object = renamed;
+        }
+        Value::Array(array) => {
+            for value in array.iter_mut() {
+                allocating_transform(value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn updates(c: &mut Criterion) {
+    let recording = recording();
+
+    let mut group = c.benchmark_group("updates");
+    // the outputs are returned so criterion drops them outside of the measurement
+
+    group.bench_function("allocating", |b| {
+        b.iter_batched(
+            || recording.updates.clone(),
+            |updates| {
+                updates
+                    .into_iter()
+                    .map(|update| {
+                        let mut value = Value::Object(update);
+                        allocating_transform(&mut value);
+                        value
+                    })
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    // warmed up on the initial like in live, every key after that is a cache hit
+    let transformer = Transformer::new(Casing::CamelCase);
+    transformer.transform(&mut recording.initial.clone());
+
+    group.bench_function("cached", |b| {
+        b.iter_batched(
+            || recording.updates.clone(),
+            |updates| {
+                updates
+                    .into_iter()
+                    .map(|mut update| transformer.transform_map(&mut update))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn initial(c: &mut Criterion) {
+    let recording = recording();
+
+    let mut group = c.benchmark_group("initial");
+
+    group.bench_function("allocating", |b| {
+        b.iter_batched(
+            || recording.initial.clone(),
+            |mut initial| {
+                allocating_transform(&mut initial);
+                initial
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    // a fresh transformer every time, the initial is where the cache is filled
+    group.bench_function("cached", |b| {
+        b.iter_batched(
+            || recording.initial.clone(),
+            |mut initial| {
+                Transformer::new(Casing::CamelCase).transform(&mut initial);
+                initial
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, updates, initial);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+//! Data derived from the feed.
+//!
+//! What the feed only ever sends the current value of is built up update by update in [`Analysis`],
+//! what the merged state already holds in full is read from it when asked for.
+//! Like the models everything here works on the transformed state, so keys are camelCase.
+
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{parse, LapCount, TimingAppData, TimingData, Topic, TrackStatus, Update};
+
+pub mod gaps;
+pub mod laps;
+pub mod pit_stops;
+pub mod race_control;
+pub mod stints;
+pub mod telemetry;
+pub mod track_status;
+
+use gaps::GapHistory;
+use laps::LapHistory;
+use pit_stops::PitStops;
+use telemetry::LatestTelemetry;
+use track_status::TrackStatusTimeline;
+
+/// the topics the trackers are fed with, the rest is not even parsed
+const TOPICS: [Topic; 6] = [
+    Topic::TimingData,
+    Topic::TimingAppData,
+    Topic::PitLaneTimeCollection,
+    Topic::CarData,
+    Topic::LapCount,
+    Topic::TrackStatus,
+];
+
+/// Everything live derives from the feed, one section per tracker.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Analysis {
+    pub laps: LapHistory,
+    pub telemetry: LatestTelemetry,
+    pub pit_stops: PitStops,
+    pub track_status: TrackStatusTimeline,
+    pub gaps: GapHistory,
+}
+
+impl Analysis {
+    /// catches up with the initial state, what was collected so far is kept
+    /// as a new initial is also sent after a reconnect to the same session
+    pub fn initial(&mut self, state: &Value) -> Result<(), serde_json::Error> {
+        let timing_data = match state.get(Topic::TimingData.key()) {
+            Some(timing_data) => parse(timing_data)?,
+            None => TimingData::default(),
+        };
+
+        let timing_app_data = match state.get(Topic::TimingAppData.key()) {
+            Some(timing_app_data) => parse(timing_app_data)?,
+            None => TimingAppData::default(),
+        };
+
+        self.laps.initial(&timing_data);
+        self.gaps.initial(&timing_data);
+        self.pit_stops.initial(&timing_data, &timing_app_data);
+
+        if let Some(pit_lane_times) = state.get(Topic::PitLaneTimeCollection.key()) {
+            self.pit_stops.pit_lane_times(&parse(pit_lane_times)?);
+        }
+
+        if let Some(car_data) = state.get(Topic::CarData.key()) {
+            self.telemetry.car_data(&parse(car_data)?);
+        }
+
+        let track_status: TrackStatus = match state.get(Topic::TrackStatus.key()) {
+            Some(track_status) => parse(track_status)?,
+            None => TrackStatus::default(),
+        };
+
+        let lap_count: LapCount = match state.get(Topic::LapCount.key()) {
+            Some(lap_count) => parse(lap_count)?,
+            None => LapCount::default(),
+        };
+
+        self.track_status.initial(&track_status, &lap_count);
+
+        Ok(())
+    }
+
+    /// feeds a transformed update like `{ "timingData": { .. } }`,
+    /// topics that fail to parse are skipped and the first error returned
+    pub fn update(
+        &mut self,
+        update: &Value,
+        timestamp: Option<&str>,
+    ) -> Result<(), serde_json::Error> {
+        let Value::Object(topics) = update else {
+            return Ok(());
+        };
+
+        let mut result = Ok(());
+
+        for (key, data) in topics {
+            if let Err(e) = self.topic(key, data, timestamp) {
+                result = result.and(Err(e));
+            }
+        }
+
+        result
+    }
+
+    fn topic(
+        &mut self,
+        key: &str,
+        data: &Value,
+        timestamp: Option<&str>,
+    ) -> Result<(), serde_json::Error> {
+        if !Topic::from_key(key).is_some_and(|topic| TOPICS.contains(&topic)) {
+            return Ok(());
+        }
+
+        match Update::parse(key, data)? {
+            Update::TimingData(timing_data) => {
+                self.laps.timing_data(&timing_data, timestamp);
+                self.gaps.timing_data(&timing_data, timestamp);
+                self.pit_stops.timing_data(&timing_data, timestamp);
+            }
+            Update::TimingAppData(timing_app_data) => {
+                self.pit_stops.timing_app_data(&timing_app_data)
+            }
+            Update::PitLaneTimeCollection(pit_lane_times) => {
+                self.pit_stops.pit_lane_times(&pit_lane_times)
+            }
+            Update::CarData(car_data) => self.telemetry.car_data(&car_data),
+            Update::LapCount(lap_count) => self.track_status.lap_count(&lap_count),
+            Update::TrackStatus(track_status) => {
+                self.track_status.track_status(&track_status, timestamp)
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// forgets everything, for when the session changes
+    pub fn reset(&mut self) {
+        *self = Analysis::default();
+    }
+}
+
+// the feed has timestamps with and without a zone and with 3 or 7 fractional digits,
+// so they only compare once parsed
+pub(crate) fn parse_utc(utc: &str) -> Option<NaiveDateTime> {
+    match DateTime::parse_from_rfc3339(utc) {
+        Ok(utc) => Some(utc.naive_utc()),
+        Err(_) => NaiveDateTime::parse_from_str(utc, "%Y-%m-%dT%H:%M:%S%.f").ok(),
+    }
+}
@@ -0,0 +1,232 @@
+use std::{collections::BTreeMap, mem};
+
+use serde::Serialize;
+
+use crate::models::{TimingData, TimingDataDriver};
+
+use super::{laps::SECTORS, parse_utc};
+
+/// the interval in seconds a driver gets DRS within
+pub const DRS_RANGE: f64 = 1.0;
+
+/// the fewest cars that make a train, the one in front included
+pub const MIN_TRAIN_LENGTH: usize = 3;
+
+/// The gaps of a driver as they were when crossing a timing line.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GapSample {
+    /// the lap the line was crossed on
+    pub lap: i64,
+    /// the sector that was finished, the last one is the finish line
+    pub sector: usize,
+    pub position: Option<i64>,
+    /// as the feed sends it, like "+12.345", "1L" or "LAP 23" for the leader
+    pub gap_to_leader: Option<String>,
+    pub interval: Option<String>,
+    /// the driver is closing in on the car ahead
+    pub catching: Option<bool>,
+    pub utc: Option<String>,
+}
+
+impl GapSample {
+    pub fn gap_to_leader_seconds(&self) -> Option<f64> {
+        self.gap_to_leader.as_deref().and_then(seconds)
+    }
+
+    pub fn interval_seconds(&self) -> Option<f64> {
+        self.interval.as_deref().and_then(seconds)
+    }
+}
+
+/// Cars each within DRS range of the one ahead when crossing the finish line.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DrsTrain {
+    pub lap: i64,
+    /// the car in front first
+    pub racing_numbers: Vec<String>,
+}
+
+/// The gap to the leader and the interval of every driver keyed by racing number, built from timingData.
+///
+/// The state only holds the latest gaps, here they are sampled at every sector so they can be charted
+/// after the fact. A sample holds the gaps last sent when the sector time or the lap count comes.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct GapHistory {
+    drivers: BTreeMap<String, DriverGaps>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+struct DriverGaps {
+    samples: Vec<GapSample>,
+    #[serde(skip)]
+    latest: GapSample,
+    /// the last `numberOfLaps`, `None` until we know where the driver is
+    #[serde(skip)]
+    completed: Option<i64>,
+}
+
+impl GapHistory {
+    /// the samples of a driver, oldest first
+    pub fn driver(&self, racing_number: &str) -> Option<&[GapSample]> {
+        self.drivers
+            .get(racing_number)
+            .map(|driver| driver.samples.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &[GapSample])> {
+        self.drivers
+            .iter()
+            .map(|(racing_number, driver)| (racing_number, driver.samples.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drivers
+            .values()
+            .all(|driver| driver.samples.is_empty())
+    }
+
+    /// syncs the lap count and the gaps with the initial, what was sampled so far is kept
+    pub fn initial(&mut self, timing_data: &TimingData) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+
+            driver.latest(line);
+            driver.completed = Some(line.number_of_laps.unwrap_or(0));
+        }
+    }
+
+    pub fn timing_data(&mut self, timing_data: &TimingData, timestamp: Option<&str>) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            self.drivers
+                .entry(racing_number.to_owned())
+                .or_default()
+                .update(line, timestamp);
+        }
+    }
+
+    /// the trains at the finish line of every lap, in order of lap and position
+    pub fn drs_trains(&self) -> Vec<DrsTrain> {
+        // the intervals at the finish line, keyed by lap and position. Two cars that swap
+        // on the line can both cross with the same position, the first to cross is the one ahead
+        let mut crossings = BTreeMap::new();
+
+        for (racing_number, driver) in &self.drivers {
+            for sample in driver.samples.iter().filter(|s| s.sector == SECTORS) {
+                if let Some(position) = sample.position {
+                    let crossed = sample.utc.as_deref().and_then(parse_utc);
+                    crossings.insert((sample.lap, position, crossed, racing_number), sample);
+                }
+            }
+        }
+
+        let mut trains = Vec::new();
+        let mut train = DrsTrain::default();
+
+        for ((lap, _, _, racing_number), sample) in crossings {
+            let in_range = sample
+                .interval_seconds()
+                .is_some_and(|interval| interval <= DRS_RANGE);
+
+            // the car starts a new train, the one it was behind is done
+            if !in_range || train.lap != lap {
+                let done = mem::replace(
+                    &mut train,
+                    DrsTrain {
+                        lap,
+                        racing_numbers: Vec::new(),
+                    },
+                );
+
+                if done.racing_numbers.len() >= MIN_TRAIN_LENGTH {
+                    trains.push(done);
+                }
+            }
+
+            train.racing_numbers.push(racing_number.to_owned());
+        }
+
+        if train.racing_numbers.len() >= MIN_TRAIN_LENGTH {
+            trains.push(train);
+        }
+
+        trains
+    }
+}
+
+impl DriverGaps {
+    fn update(&mut self, line: &TimingDataDriver, timestamp: Option<&str>) {
+        self.latest(line);
+
+        let Some(completed) = self.completed else {
+            // a driver we never had the initial for, we don't know which lap they are on yet
+            self.completed = line.number_of_laps;
+            return;
+        };
+
+        for (index, sector) in line.sectors.iter().flat_map(|sectors| sectors.iter()) {
+            let timed = sector.value.as_ref().is_some_and(|value| !value.is_empty());
+
+            // the last sector ends with the lap count going up, which comes first
+            if timed && *index < SECTORS - 1 {
+                self.sample(completed + 1, index + 1, timestamp);
+            }
+        }
+
+        if let Some(number) = line.number_of_laps.filter(|number| *number > completed) {
+            self.sample(number, SECTORS, timestamp);
+            self.completed = Some(number);
+        }
+    }
+
+    fn latest(&mut self, line: &TimingDataDriver) {
+        if let Some(position) = line.position.as_deref().and_then(|p| p.parse().ok()) {
+            self.latest.position = Some(position);
+        }
+
+        if let Some(gap) = &line.gap_to_leader {
+            self.latest.gap_to_leader = Some(gap.to_owned());
+        }
+
+        if let Some(interval) = &line.interval_to_position_ahead {
+            if let Some(value) = &interval.value {
+                self.latest.interval = Some(value.to_owned());
+            }
+
+            if interval.catching.is_some() {
+                self.latest.catching = interval.catching;
+            }
+        }
+    }
+
+    fn sample(&mut self, lap: i64, sector: usize, timestamp: Option<&str>) {
+        // outside of races there are no gaps to sample
+        if self.latest.gap_to_leader.is_none() && self.latest.interval.is_none() {
+            return;
+        }
+
+        // a sector time sent again
+        if self
+            .samples
+            .last()
+            .is_some_and(|last| last.lap == lap && last.sector == sector)
+        {
+            return;
+        }
+
+        self.samples.push(GapSample {
+            lap,
+            sector,
+            utc: timestamp.map(str::to_owned),
+            ..self.latest.clone()
+        });
+    }
+}
+
+// "+1.234" in seconds, laps and the leader's "LAP 23" have none
+fn seconds(value: &str) -> Option<f64> {
+    value.trim().trim_start_matches('+').parse().ok()
+}
@@ -0,0 +1,200 @@
+use std::{collections::BTreeMap, mem};
+
+use serde::Serialize;
+
+use crate::models::{LapTime, TimingData, TimingDataDriver};
+
+/// every lap is timed in three sectors
+pub const SECTORS: usize = 3;
+
+/// A lap a driver finished.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lap {
+    /// the number of the lap, the first one is 1
+    pub number: i64,
+    pub time: Option<String>,
+    pub sectors: [Option<String>; SECTORS],
+    pub speeds: SpeedTraps,
+    /// the driver went into the pit lane on this lap
+    pub pit_in: bool,
+    /// the driver came out of the pit lane on this lap
+    pub pit_out: bool,
+    /// when the lap was finished, the timestamp of the update
+    pub utc: Option<String>,
+}
+
+impl Lap {
+    // nothing was timed yet, the pit flags don't count
+    fn is_empty(&self) -> bool {
+        self.time.is_none()
+            && self.sectors.iter().all(Option::is_none)
+            && self.speeds == SpeedTraps::default()
+    }
+}
+
+/// The speeds in km/h, as strings like the feed sends them.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTraps {
+    /// intermediate 1
+    pub i1: Option<String>,
+    /// intermediate 2
+    pub i2: Option<String>,
+    /// finish line
+    pub fl: Option<String>,
+    /// speed trap
+    pub st: Option<String>,
+}
+
+/// The laps of every driver keyed by racing number, built from timingData.
+///
+/// A lap is finished when `numberOfLaps` goes up, everything timed since the last one belongs to it.
+/// The last sector, the finish line speed and the lap time can come a moment after the lap count,
+/// they still go to the finished lap as long as nothing of the next one was timed.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct LapHistory {
+    drivers: BTreeMap<String, DriverLaps>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+struct DriverLaps {
+    laps: Vec<Lap>,
+    /// the lap being driven, its number is only known once it is finished
+    #[serde(skip)]
+    current: Lap,
+    /// the last `numberOfLaps`, `None` until we know where the driver is
+    #[serde(skip)]
+    completed: Option<i64>,
+}
+
+impl LapHistory {
+    /// the finished laps of a driver, oldest first
+    pub fn driver(&self, racing_number: &str) -> Option<&[Lap]> {
+        self.drivers
+            .get(racing_number)
+            .map(|driver| driver.laps.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &[Lap])> {
+        self.drivers
+            .iter()
+            .map(|(racing_number, driver)| (racing_number, driver.laps.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drivers.values().all(|driver| driver.laps.is_empty())
+    }
+
+    /// syncs the lap count with the initial, laps already in progress are not recorded
+    pub fn initial(&mut self, timing_data: &TimingData) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+
+            // no lap count in the initial means no lap was finished yet
+            driver.completed = Some(line.number_of_laps.unwrap_or(0));
+            driver.current = Lap::default();
+        }
+    }
+
+    pub fn timing_data(&mut self, timing_data: &TimingData, timestamp: Option<&str>) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            self.drivers
+                .entry(racing_number.to_owned())
+                .or_default()
+                .update(line, timestamp);
+        }
+    }
+}
+
+impl DriverLaps {
+    fn update(&mut self, line: &TimingDataDriver, timestamp: Option<&str>) {
+        let finished = line
+            .number_of_laps
+            .filter(|number| self.completed.is_some_and(|completed| *number > completed));
+
+        if line.in_pit == Some(true) {
+            self.current.pit_in = true;
+        }
+
+        if line.pit_out == Some(true) {
+            self.current.pit_out = true;
+        }
+
+        for (index, sector) in line.sectors.iter().flat_map(|sectors| sectors.iter()) {
+            let (Some(value), true) = (non_empty(&sector.value), *index < SECTORS) else {
+                continue;
+            };
+
+            let lap = match *index == SECTORS - 1 {
+                true => self.lap(finished.is_some(), |lap| lap.sectors[SECTORS - 1].is_none()),
+                false => &mut self.current,
+            };
+
+            lap.sectors[*index] = Some(value);
+        }
+
+        if let Some(speeds) = &line.speeds {
+            if let Some(i1) = value(&speeds.i1) {
+                self.current.speeds.i1 = Some(i1);
+            }
+
+            if let Some(i2) = value(&speeds.i2) {
+                self.current.speeds.i2 = Some(i2);
+            }
+
+            if let Some(st) = value(&speeds.st) {
+                self.current.speeds.st = Some(st);
+            }
+
+            if let Some(fl) = value(&speeds.fl) {
+                self.lap(finished.is_some(), |lap| lap.speeds.fl.is_none())
+                    .speeds
+                    .fl = Some(fl);
+            }
+        }
+
+        if let Some(time) = value(&line.last_lap_time) {
+            self.lap(finished.is_some(), |lap| lap.time.is_none()).time = Some(time);
+        }
+
+        match (finished, line.number_of_laps) {
+            (Some(number), _) => {
+                let mut lap = mem::take(&mut self.current);
+                lap.number = number;
+                lap.utc = timestamp.map(str::to_owned);
+
+                self.laps.push(lap);
+                self.completed = Some(number);
+            }
+            // a driver we never had the initial for, what was timed so far is only part of a lap
+            (None, Some(number)) if self.completed.is_none() => {
+                self.current = Lap::default();
+                self.completed = Some(number);
+            }
+            _ => {}
+        }
+    }
+
+    // the lap a value of the end of a lap goes to, the finished one when it is late
+    fn lap(&mut self, finishing: bool, missing: impl Fn(&Lap) -> bool) -> &mut Lap {
+        if !finishing && self.current.is_empty() {
+            if let Some(last) = self.laps.last_mut().filter(|last| missing(last)) {
+                return last;
+            }
+        }
+
+        &mut self.current
+    }
+}
+
+fn value(time: &Option<LapTime>) -> Option<String> {
+    time.as_ref().and_then(|time| non_empty(&time.value))
+}
+
+// the feed clears values with an empty string at the start of a lap
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().filter(|value| !value.is_empty()).cloned()
+}
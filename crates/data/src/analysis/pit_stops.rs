@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::models::{PitLaneTimeCollection, TimingAppData, TimingData};
+
+/// A visit of the pit lane.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PitStop {
+    /// the first stop is 1
+    pub number: usize,
+    /// the lap the driver came in on
+    pub lap: Option<i64>,
+    /// when the driver entered the pit lane, `None` for stops we only know the lane time of
+    pub entry: Option<String>,
+    /// when the driver left the pit lane again
+    pub exit: Option<String>,
+    /// the time from pit entry to pit exit in seconds, as pitLaneTimeCollection sends it
+    pub lane_time: Option<String>,
+    /// a new set of tyres was fitted
+    pub tyre_change: bool,
+    pub compound: Option<String>,
+    pub new_tyres: Option<bool>,
+    /// the index of the stint the stop started
+    #[serde(skip)]
+    stint: Option<usize>,
+}
+
+/// The pit stops of every driver keyed by racing number.
+///
+/// A stop starts when timingData says the driver is `inPit` and ends when they are out again.
+/// The lane time of pitLaneTimeCollection is matched by lap, as the feed deletes it a while
+/// after the stop. A stint that starts during or after a stop is its tyre change.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct PitStops {
+    drivers: BTreeMap<String, DriverStops>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+struct DriverStops {
+    stops: Vec<PitStop>,
+    #[serde(skip)]
+    in_pit: bool,
+    /// the last `numberOfLaps`
+    #[serde(skip)]
+    completed: i64,
+    /// how many stints the driver had so far, `None` until we know
+    #[serde(skip)]
+    stints: Option<usize>,
+}
+
+impl PitStops {
+    /// the stops of a driver, oldest first
+    pub fn driver(&self, racing_number: &str) -> Option<&[PitStop]> {
+        self.drivers
+            .get(racing_number)
+            .map(|driver| driver.stops.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &[PitStop])> {
+        self.drivers
+            .iter()
+            .map(|(racing_number, driver)| (racing_number, driver.stops.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drivers.values().all(|driver| driver.stops.is_empty())
+    }
+
+    /// syncs with the initial, a stop already in progress is only recorded once its lane time comes
+    pub fn initial(&mut self, timing_data: &TimingData, timing_app_data: &TimingAppData) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+
+            driver.in_pit = line.in_pit == Some(true);
+            driver.completed = line.number_of_laps.unwrap_or(driver.completed);
+        }
+
+        for (racing_number, line) in timing_app_data.lines.iter().flatten() {
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+            driver.stints = Some(line.stints.as_ref().map_or(0, |stints| stints.len()));
+        }
+    }
+
+    pub fn timing_data(&mut self, timing_data: &TimingData, timestamp: Option<&str>) {
+        for (racing_number, line) in timing_data.lines.iter().flatten() {
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+
+            // the lap count goes up while in the pit lane on most tracks, the stop is on the lap before
+            if line.in_pit == Some(true) && !driver.in_pit {
+                let number = driver.stops.len() + 1;
+
+                driver.stops.push(PitStop {
+                    number,
+                    lap: Some(driver.completed + 1),
+                    entry: timestamp.map(str::to_owned),
+                    ..PitStop::default()
+                });
+            }
+
+            let left = (driver.in_pit && line.in_pit == Some(false)) || line.pit_out == Some(true);
+
+            if let Some(in_pit) = line.in_pit {
+                driver.in_pit = in_pit;
+            }
+
+            if let Some(number) = line.number_of_laps {
+                driver.completed = number;
+            }
+
+            if left {
+                if let Some(stop) = driver.stops.last_mut().filter(|stop| stop.exit.is_none()) {
+                    stop.exit = timestamp.map(str::to_owned);
+                }
+            }
+        }
+    }
+
+    pub fn pit_lane_times(&mut self, collection: &PitLaneTimeCollection) {
+        for (racing_number, time) in collection.pit_times.iter().flatten() {
+            let Some(duration) = time.duration.as_ref().filter(|d| !d.is_empty()) else {
+                continue;
+            };
+
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+            let lap = time.lap.as_deref().and_then(|lap| lap.parse::<i64>().ok());
+
+            // the same lap, or the lap after when the count went up before the stop was seen
+            let matching = driver.stops.iter().rposition(|stop| match (stop.lap, lap) {
+                (Some(stop_lap), Some(lap)) => stop_lap == lap || stop_lap + 1 == lap,
+                _ => stop.lane_time.is_none(),
+            });
+
+            match matching {
+                Some(index) => driver.stops[index].lane_time = Some(duration.to_owned()),
+                None => {
+                    let number = driver.stops.len() + 1;
+
+                    driver.stops.push(PitStop {
+                        number,
+                        lap,
+                        lane_time: Some(duration.to_owned()),
+                        ..PitStop::default()
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn timing_app_data(&mut self, timing_app_data: &TimingAppData) {
+        for (racing_number, line) in timing_app_data.lines.iter().flatten() {
+            let Some(stints) = &line.stints else {
+                continue;
+            };
+
+            let driver = self.drivers.entry(racing_number.to_owned()).or_default();
+
+            for (index, stint) in stints.iter() {
+                let new_stint = driver.stints.is_some_and(|known| *index >= known);
+
+                if new_stint {
+                    if let Some(stop) = driver.stops.last_mut().filter(|stop| stop.stint.is_none())
+                    {
+                        stop.tyre_change = true;
+                        stop.stint = Some(*index);
+                    }
+                }
+
+                // the compound can come after the stint itself
+                if let Some(stop) = driver
+                    .stops
+                    .iter_mut()
+                    .find(|stop| stop.stint == Some(*index))
+                {
+                    if stint.compound.is_some() {
+                        stop.compound = stint.compound.clone();
+                    }
+
+                    if let Some(new) = stint.new.as_deref().and_then(|new| new.parse().ok()) {
+                        stop.new_tyres = Some(new);
+                    }
+                }
+            }
+
+            if let Some(last) = stints.0.keys().next_back() {
+                driver.stints = Some(driver.stints.unwrap_or(0).max(last + 1));
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::models::{RaceControlMessage, RaceControlMessages};
+
+/// What a race control message is about, read from its category, fields and text.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum RaceControlEvent {
+    Flag {
+        /// as the feed names it, like "YELLOW", "DOUBLE YELLOW" or "CLEAR"
+        flag: String,
+        /// "Track", "Sector" or "Driver"
+        scope: Option<String>,
+        sector: Option<i64>,
+        /// for blue and black and white flags
+        racing_number: Option<String>,
+    },
+    Penalty {
+        racing_number: Option<String>,
+        penalty: Penalty,
+        /// the seconds of time and stop and go penalties
+        seconds: Option<i64>,
+        /// the places of grid penalties
+        places: Option<i64>,
+        reason: Option<String>,
+    },
+    /// a time, drive through or stop and go penalty that was taken
+    PenaltyServed {
+        racing_number: Option<String>,
+        /// when the message says which one
+        penalty: Option<Penalty>,
+        seconds: Option<i64>,
+    },
+    Investigation {
+        status: InvestigationStatus,
+        /// every car involved
+        racing_numbers: Vec<String>,
+        reason: Option<String>,
+    },
+    LapDeleted {
+        racing_number: Option<String>,
+        /// the lap time that was deleted
+        time: Option<String>,
+        lap: Option<i64>,
+        reason: Option<String>,
+    },
+    Drs {
+        enabled: bool,
+    },
+    SafetyCar {
+        /// a virtual safety car
+        r#virtual: bool,
+        status: SafetyCarStatus,
+    },
+    /// anything we don't know how to read, the message itself is still there
+    Other,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Penalty {
+    Time,
+    DriveThrough,
+    StopGo,
+    Grid,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InvestigationStatus {
+    /// the stewards noted an incident, it may or may not be looked into
+    Noted,
+    Opened,
+    /// no further action or investigation
+    Closed,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SafetyCarStatus {
+    Deployed,
+    /// the safety car comes in at the end of this lap
+    InThisLap,
+    /// the virtual safety car is about to end
+    Ending,
+}
+
+/// The race control messages as the feed sent them and what each of them is about.
+///
+/// Like the stints this is read from the state, which keeps every message.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaceControl {
+    pub messages: Vec<RaceControlMessage>,
+    /// the event of the message at the same index
+    pub events: Vec<RaceControlEvent>,
+}
+
+impl RaceControl {
+    pub fn new(messages: &RaceControlMessages) -> Self {
+        let messages: Vec<RaceControlMessage> = messages
+            .messages
+            .iter()
+            .flat_map(|messages| messages.values())
+            .cloned()
+            .collect();
+
+        let events = messages.iter().map(RaceControlEvent::classify).collect();
+
+        RaceControl { messages, events }
+    }
+}
+
+// "1 (VER)", every car named in a message
+static CAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+) \([A-Z]{3}\)").unwrap());
+
+static TIME_PENALTY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d+) SECOND (TIME|STOP/GO|STOP AND GO) PENALTY FOR CAR (\d+)").unwrap()
+});
+static DRIVE_THROUGH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"DRIVE THROUGH PENALTY FOR CAR (\d+)").unwrap());
+static GRID_PENALTY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+) PLACE GRID PENALTY FOR CAR (\d+)").unwrap());
+
+// "CAR 14 (ALO) TIME 1:31.522 DELETED - TRACK LIMITS AT TURN 4 LAP 12 15:12:33"
+static DELETED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"CAR (\d+) \([A-Z]{3}\) (?:TIME ([\d:.]+) DELETED|LAP DELETED)").unwrap()
+});
+static DELETED_LAP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" LAP (\d+)(?: \d{2}:\d{2}:\d{2})?$").unwrap());
+
+impl RaceControlEvent {
+    pub fn classify(message: &RaceControlMessage) -> RaceControlEvent {
+        let text = message.message.as_deref().unwrap_or_default();
+
+        match message.category.as_deref() {
+            Some("Flag") => flag(message, text),
+            Some("Drs") => drs(message, text),
+            Some("SafetyCar") => safety_car(message, text),
+            _ if text.contains("PENALTY SERVED") => penalty_served(text),
+            _ if text.contains("PENALTY") && !text.contains("NO FURTHER") => penalty(text),
+            _ if text.contains("DELETED") => lap_deleted(message, text),
+            _ if text.starts_with("FIA STEWARDS") => investigation(text),
+            _ => RaceControlEvent::Other,
+        }
+    }
+}
+
+fn flag(message: &RaceControlMessage, text: &str) -> RaceControlEvent {
+    let Some(flag) = &message.flag else {
+        return RaceControlEvent::Other;
+    };
+
+    let racing_number = message
+        .racing_number
+        .clone()
+        .or_else(|| first_car(text).filter(|_| message.scope.as_deref() == Some("Driver")));
+
+    RaceControlEvent::Flag {
+        flag: flag.to_owned(),
+        scope: message.scope.clone(),
+        sector: message.sector,
+        racing_number,
+    }
+}
+
+fn drs(message: &RaceControlMessage, text: &str) -> RaceControlEvent {
+    let status = message.status.as_deref().unwrap_or(text);
+
+    match (status.contains("ENABLED"), status.contains("DISABLED")) {
+        (true, _) => RaceControlEvent::Drs { enabled: true },
+        (_, true) => RaceControlEvent::Drs { enabled: false },
+        _ => RaceControlEvent::Other,
+    }
+}
+
+fn safety_car(message: &RaceControlMessage, text: &str) -> RaceControlEvent {
+    let r#virtual = message.mode.as_deref().unwrap_or(text).contains("VIRTUAL");
+    let status = message.status.as_deref().unwrap_or(text);
+
+    let status = if status.contains("DEPLOYED") {
+        SafetyCarStatus::Deployed
+    } else if status.contains("IN THIS LAP") {
+        SafetyCarStatus::InThisLap
+    } else if status.contains("ENDING") {
+        SafetyCarStatus::Ending
+    } else {
+        return RaceControlEvent::Other;
+    };
+
+    RaceControlEvent::SafetyCar { r#virtual, status }
+}
+
+fn penalty(text: &str) -> RaceControlEvent {
+    let Some(given) = given_penalty(text) else {
+        return RaceControlEvent::Other;
+    };
+
+    RaceControlEvent::Penalty {
+        racing_number: Some(given.racing_number),
+        penalty: given.penalty,
+        seconds: given.seconds,
+        places: given.places,
+        reason: reason(text),
+    }
+}
+
+// "PENALTY SERVED: 5 SECOND TIME PENALTY FOR CAR 1 (VER) - CAUSING A COLLISION"
+fn penalty_served(text: &str) -> RaceControlEvent {
+    match given_penalty(text) {
+        Some(given) => RaceControlEvent::PenaltyServed {
+            racing_number: Some(given.racing_number),
+            penalty: Some(given.penalty),
+            seconds: given.seconds,
+        },
+        None => RaceControlEvent::PenaltyServed {
+            racing_number: first_car(text),
+            penalty: None,
+            seconds: None,
+        },
+    }
+}
+
+struct GivenPenalty {
+    racing_number: String,
+    penalty: Penalty,
+    seconds: Option<i64>,
+    places: Option<i64>,
+}
+
+fn given_penalty(text: &str) -> Option<GivenPenalty> {
+    if let Some(captures) = TIME_PENALTY.captures(text) {
+        return Some(GivenPenalty {
+            racing_number: captures[3].to_owned(),
+            penalty: match &captures[2] {
+                "TIME" => Penalty::Time,
+                _ => Penalty::StopGo,
+            },
+            seconds: captures[1].parse().ok(),
+            places: None,
+        });
+    }
+
+    if let Some(captures) = DRIVE_THROUGH.captures(text) {
+        return Some(GivenPenalty {
+            racing_number: captures[1].to_owned(),
+            penalty: Penalty::DriveThrough,
+            seconds: None,
+            places: None,
+        });
+    }
+
+    GRID_PENALTY.captures(text).map(|captures| GivenPenalty {
+        racing_number: captures[2].to_owned(),
+        penalty: Penalty::Grid,
+        seconds: None,
+        places: captures[1].parse().ok(),
+    })
+}
+
+fn lap_deleted(message: &RaceControlMessage, text: &str) -> RaceControlEvent {
+    let Some(captures) = DELETED.captures(text) else {
+        return RaceControlEvent::Other;
+    };
+
+    // not every message names the lap, then it is the one the message was sent on
+    let lap = DELETED_LAP
+        .captures(text)
+        .and_then(|lap| lap[1].parse().ok())
+        .or(message.lap);
+
+    // the reason is followed by the lap and the time of day
+    let reason = reason(text).map(|reason| match DELETED_LAP.find(&reason) {
+        Some(suffix) => reason[..suffix.start()].to_owned(),
+        None => reason,
+    });
+
+    RaceControlEvent::LapDeleted {
+        racing_number: Some(captures[1].to_owned()),
+        time: captures.get(2).map(|time| time.as_str().to_owned()),
+        lap,
+        reason,
+    }
+}
+
+fn investigation(text: &str) -> RaceControlEvent {
+    let status = if text.contains("UNDER INVESTIGATION") || text.contains("WILL BE INVESTIGATED") {
+        InvestigationStatus::Opened
+    } else if text.contains("NO FURTHER") {
+        InvestigationStatus::Closed
+    } else if text.contains("NOTED") {
+        InvestigationStatus::Noted
+    } else {
+        return RaceControlEvent::Other;
+    };
+
+    RaceControlEvent::Investigation {
+        status,
+        racing_numbers: CAR
+            .captures_iter(text)
+            .map(|captures| captures[1].to_owned())
+            .collect(),
+        reason: reason(text),
+    }
+}
+
+fn first_car(text: &str) -> Option<String> {
+    CAR.captures(text).map(|captures| captures[1].to_owned())
+}
+
+// what comes after the last " - ", like "CAUSING A COLLISION"
+fn reason(text: &str) -> Option<String> {
+    text.rsplit_once(" - ")
+        .map(|(_, reason)| reason.trim().to_owned())
+        .filter(|reason| !reason.is_empty())
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::models::{SessionInfo, Stint, TimingAppData, TimingAppDataDriver};
+
+/// the compounds of a dry race, two of them have to be used
+pub const DRY_COMPOUNDS: [&str; 3] = ["SOFT", "MEDIUM", "HARD"];
+
+/// the compounds for a wet track, a driver who used one no longer has to use two dry compounds
+pub const WET_COMPOUNDS: [&str; 2] = ["INTERMEDIATE", "WET"];
+
+/// A set of tyres a driver was or still is on.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StintSummary {
+    /// the first stint is 1
+    pub number: usize,
+    pub compound: Option<String>,
+    /// the tyres were new when fitted, `None` when the feed did not say
+    pub new: Option<bool>,
+    /// the first lap driven on the tyres
+    pub start_lap: i64,
+    /// the last lap finished on the tyres, `None` when none was yet
+    pub end_lap: Option<i64>,
+    /// the laps driven on the tyres in this stint
+    pub laps: i64,
+    /// the age of the tyres, laps from earlier sessions included
+    pub tyre_laps: i64,
+    /// the driver is still on these tyres
+    pub current: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverStints {
+    pub stints: Vec<StintSummary>,
+    /// the different dry compounds used so far
+    pub dry_compounds: Vec<String>,
+    /// a race, no intermediates or wets used and only one dry compound so far
+    pub needs_second_compound: bool,
+}
+
+/// The stints of every driver and whether they did their two compounds.
+///
+/// Unlike the laps there is nothing to collect, the state keeps the whole list of stints
+/// (deletions and index patches included), so this is read from it when asked for.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Stints {
+    /// a grand prix, where the drivers who stay on dry tyres have to use two compounds
+    pub race: bool,
+    /// keyed by racing number
+    pub drivers: BTreeMap<String, DriverStints>,
+}
+
+impl Stints {
+    pub fn new(timing_app_data: &TimingAppData, session_info: Option<&SessionInfo>) -> Self {
+        let race = is_race(session_info);
+
+        let drivers = timing_app_data
+            .lines
+            .iter()
+            .flatten()
+            .map(|(racing_number, line)| {
+                let driver = DriverStints::new(&stints(line), race);
+                (racing_number.to_owned(), driver)
+            })
+            .collect();
+
+        Stints { race, drivers }
+    }
+}
+
+impl DriverStints {
+    fn new(stints: &[&Stint], race: bool) -> Self {
+        let mut start_lap = 1;
+        let mut summaries = Vec::with_capacity(stints.len());
+
+        for (index, stint) in stints.iter().enumerate() {
+            let tyre_laps = stint.total_laps.unwrap_or(0);
+            let laps = (tyre_laps - stint.start_laps.unwrap_or(0)).max(0);
+
+            summaries.push(StintSummary {
+                number: index + 1,
+                compound: stint.compound.clone(),
+                new: stint.new.as_deref().and_then(|new| new.parse().ok()),
+                start_lap,
+                end_lap: (laps > 0).then(|| start_lap + laps - 1),
+                laps,
+                tyre_laps,
+                current: index + 1 == stints.len(),
+            });
+
+            start_lap += laps;
+        }
+
+        let mut dry_compounds: Vec<String> = Vec::new();
+
+        for compound in stints.iter().filter_map(|stint| stint.compound.as_deref()) {
+            if DRY_COMPOUNDS.contains(&compound) && !dry_compounds.iter().any(|c| c == compound) {
+                dry_compounds.push(compound.to_owned());
+            }
+        }
+
+        // the rule is per driver, others going out on intermediates don't change it
+        let wet = stints
+            .iter()
+            .filter_map(|stint| stint.compound.as_deref())
+            .any(|compound| WET_COMPOUNDS.contains(&compound));
+
+        DriverStints {
+            stints: summaries,
+            needs_second_compound: race && !wet && dry_compounds.len() < 2,
+            dry_compounds,
+        }
+    }
+}
+
+fn stints(line: &TimingAppDataDriver) -> Vec<&Stint> {
+    line.stints
+        .iter()
+        .flat_map(|stints| stints.values())
+        .collect()
+}
+
+// the two compound rule is for the grand prix, not for sprints
+fn is_race(session_info: Option<&SessionInfo>) -> bool {
+    session_info.is_some_and(|info| {
+        info.kind.as_deref() == Some("Race") && info.name.as_deref() != Some("Sprint")
+    })
+}
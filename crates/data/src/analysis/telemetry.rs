@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::models::{CarData, Telemetry};
+
+use super::parse_utc;
+
+/// The latest sample of every car keyed by racing number, built from carData.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct LatestTelemetry {
+    drivers: BTreeMap<String, Telemetry>,
+}
+
+impl LatestTelemetry {
+    pub fn driver(&self, racing_number: &str) -> Option<&Telemetry> {
+        self.drivers.get(racing_number)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Telemetry)> {
+        self.drivers.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+
+    pub fn car_data(&mut self, car_data: &CarData) {
+        for entry in car_data.entries.iter().flat_map(|entries| entries.values()) {
+            for (racing_number, sample) in entry.telemetry() {
+                match self.drivers.get_mut(racing_number) {
+                    // a late batch must not go back in time
+                    Some(latest) if is_newer(&latest.utc, &sample.utc) => {}
+                    Some(latest) => *latest = sample,
+                    None => {
+                        self.drivers.insert(racing_number.to_owned(), sample);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_newer(utc: &Option<String>, than: &Option<String>) -> bool {
+    utc.as_deref().and_then(parse_utc) > than.as_deref().and_then(parse_utc)
+}
@@ -0,0 +1,153 @@
+use serde::Serialize;
+
+use crate::models::{LapCount, TrackStatus};
+
+use super::parse_utc;
+
+/// A change of the track status.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    /// the status code, "1" is all clear
+    pub status: String,
+    /// like "AllClear", "Yellow" or "SCDeployed"
+    pub message: Option<String>,
+    /// `None` for the status the session was in when we joined
+    pub utc: Option<String>,
+    /// the lap the leader was on
+    pub lap: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NeutralisationKind {
+    Yellow,
+    SafetyCar,
+    /// includes the ending phase, "7"
+    VirtualSafetyCar,
+    RedFlag,
+}
+
+impl NeutralisationKind {
+    pub fn from_status(status: &str) -> Option<Self> {
+        match status {
+            "2" => Some(NeutralisationKind::Yellow),
+            "4" => Some(NeutralisationKind::SafetyCar),
+            "5" => Some(NeutralisationKind::RedFlag),
+            "6" | "7" => Some(NeutralisationKind::VirtualSafetyCar),
+            _ => None,
+        }
+    }
+}
+
+/// A stretch of the session the track was not green.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Neutralisation {
+    pub kind: NeutralisationKind,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub start_lap: Option<i64>,
+    pub end_lap: Option<i64>,
+    /// in seconds, `None` while ongoing or when either end is not known
+    pub duration: Option<f64>,
+    pub ongoing: bool,
+}
+
+/// Every track status of the session and the neutralisations they make up.
+///
+/// The state only holds the current status, so this is collected update by update.
+/// The times are the ones of the feed, replaying a recording gives the timeline of the session.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackStatusTimeline {
+    pub changes: Vec<StatusChange>,
+    pub neutralisations: Vec<Neutralisation>,
+    /// the current lap from lapCount
+    #[serde(skip)]
+    lap: Option<i64>,
+}
+
+impl TrackStatusTimeline {
+    /// the status when joining is recorded without a time, as is a change missed while away
+    pub fn initial(&mut self, track_status: &TrackStatus, lap_count: &LapCount) {
+        self.lap_count(lap_count);
+        self.track_status(track_status, None);
+    }
+
+    pub fn lap_count(&mut self, lap_count: &LapCount) {
+        if let Some(lap) = lap_count.current_lap {
+            self.lap = Some(lap);
+        }
+    }
+
+    pub fn track_status(&mut self, track_status: &TrackStatus, timestamp: Option<&str>) {
+        let Some(status) = &track_status.status else {
+            return;
+        };
+
+        if self
+            .changes
+            .last()
+            .is_some_and(|change| &change.status == status)
+        {
+            return;
+        }
+
+        self.changes.push(StatusChange {
+            status: status.to_owned(),
+            message: track_status.message.clone(),
+            utc: timestamp.map(str::to_owned),
+            lap: self.lap,
+        });
+
+        let kind = NeutralisationKind::from_status(status);
+        let ongoing = self
+            .neutralisations
+            .last_mut()
+            .filter(|neutralisation| neutralisation.ongoing);
+
+        match ongoing {
+            // the virtual safety car ending is still the same period
+            Some(neutralisation) if Some(neutralisation.kind) == kind => return,
+            Some(neutralisation) => {
+                neutralisation.end = timestamp.map(str::to_owned);
+                neutralisation.end_lap = self.lap;
+                neutralisation.duration = seconds_between(
+                    neutralisation.start.as_deref(),
+                    neutralisation.end.as_deref(),
+                );
+                neutralisation.ongoing = false;
+            }
+            None => {}
+        }
+
+        if let Some(kind) = kind {
+            self.neutralisations.push(Neutralisation {
+                kind,
+                start: timestamp.map(str::to_owned),
+                end: None,
+                start_lap: self.lap,
+                end_lap: None,
+                duration: None,
+                ongoing: true,
+            });
+        }
+    }
+
+    /// the total time spent under a kind of neutralisation, finished periods only
+    pub fn total(&self, kind: NeutralisationKind) -> f64 {
+        self.neutralisations
+            .iter()
+            .filter(|neutralisation| neutralisation.kind == kind)
+            .filter_map(|neutralisation| neutralisation.duration)
+            .sum()
+    }
+}
+
+fn seconds_between(start: Option<&str>, end: Option<&str>) -> Option<f64> {
+    let start = parse_utc(start?)?;
+    let end = parse_utc(end?)?;
+
+    Some((end - start).num_milliseconds() as f64 / 1000.0)
+}
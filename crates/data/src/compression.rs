@@ -1,24 +1,77 @@
 use base64::Engine;
+use flate2::read::DeflateDecoder;
 use flate2::write::DeflateEncoder;
 use flate2::Compression;
-use std::io::prelude::*;
+use serde_json::Value;
+use std::{fmt, io::prelude::*};
 
-pub fn deflate(data: String) -> Option<String> {
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Base64(base64::DecodeError),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to (de)compress data: {}", e),
+            Error::Base64(e) => write!(f, "invalid base64: {}", e),
+            Error::Json(e) => write!(f, "inflated data is not valid json: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Base64(e) => Some(e),
+            Error::Json(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Error::Base64(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+pub fn deflate(data: String) -> Result<String, Error> {
     // Create a ZlibEncoder
     let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
 
     // Write the JSON string into the encoder
-    match encoder.write_all(data.as_bytes()) {
-        Ok(_) => (),
-        Err(_) => return None,
-    }
+    encoder.write_all(data.as_bytes())?;
 
     // Finish the encoding process
-    let encoded_bytes = match encoder.finish() {
-        Ok(bytes) => bytes,
-        Err(_) => return None,
-    };
+    let encoded_bytes = encoder.finish()?;
 
     // Convert the byte array to base64
-    Some(base64::engine::general_purpose::STANDARD.encode(encoded_bytes))
+    Ok(base64::engine::general_purpose::STANDARD.encode(encoded_bytes))
+}
+
+pub fn inflate(data: &str) -> Result<Value, Error> {
+    // Convert the base64 back to the deflated bytes
+    let encoded_bytes = base64::engine::general_purpose::STANDARD.decode(data)?;
+
+    // Decode the raw deflate stream, same format as the .z topics from f1 and our own deflate
+    let mut decoder = DeflateDecoder::new(encoded_bytes.as_slice());
+    let mut json = String::new();
+    decoder.read_to_string(&mut json)?;
+
+    Ok(serde_json::from_str(&json)?)
 }
@@ -1,3 +1,6 @@
+pub mod analysis;
 pub mod compression;
+pub mod diff;
 pub mod merge;
+pub mod models;
 pub mod transformer;
@@ -0,0 +1,245 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The RFC 7386 merge patch that turns `from` into `to`.
+///
+/// Keys that are gone become `null`, everything that changed is sent whole
+/// unless both sides are objects, then only the changed keys are.
+/// Like every merge patch it can't set a value to `null` and replaces arrays as a whole.
+pub fn merge_patch(from: &Value, to: &Value) -> Value {
+    let (Value::Object(from), Value::Object(to)) = (from, to) else {
+        return to.clone();
+    };
+
+    let mut patch = Map::new();
+
+    for key in from.keys() {
+        if !to.contains_key(key) {
+            patch.insert(key.to_owned(), Value::Null);
+        }
+    }
+
+    for (key, value) in to {
+        match from.get(key) {
+            Some(prev) if prev == value => {}
+            Some(prev) => {
+                patch.insert(key.to_owned(), merge_patch(prev, value));
+            }
+            None => {
+                patch.insert(key.to_owned(), value.clone());
+            }
+        }
+    }
+
+    Value::Object(patch)
+}
+
+/// applies an RFC 7386 merge patch
+pub fn apply_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                apply_merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// A single RFC 6902 operation, the diff only ever produces these three.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// The RFC 6902 json patch that turns `from` into `to`.
+///
+/// Objects are compared key by key and arrays index by index,
+/// items added to the end of an array are added, surplus items removed from the back.
+pub fn json_patch(from: &Value, to: &Value) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    diff(from, to, &mut String::new(), &mut operations);
+    operations
+}
+
+fn diff(from: &Value, to: &Value, path: &mut String, operations: &mut Vec<Operation>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            for key in from.keys() {
+                if !to.contains_key(key) {
+                    operations.push(Operation::Remove {
+                        path: child(path, key),
+                    });
+                }
+            }
+
+            for (key, value) in to {
+                match from.get(key) {
+                    Some(prev) => {
+                        let len = path.len();
+                        path.push('/');
+                        path.push_str(&escape(key));
+                        diff(prev, value, path, operations);
+                        path.truncate(len);
+                    }
+                    None => operations.push(Operation::Add {
+                        path: child(path, key),
+                        value: value.clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (index, (prev, value)) in from.iter().zip(to).enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&index.to_string());
+                diff(prev, value, path, operations);
+                path.truncate(len);
+            }
+
+            // always the same index, every remove moves the rest forward
+            for _ in to.len()..from.len() {
+                operations.push(Operation::Remove {
+                    path: child(path, &to.len().to_string()),
+                });
+            }
+
+            for (index, value) in to.iter().enumerate().skip(from.len()) {
+                operations.push(Operation::Add {
+                    path: child(path, &index.to_string()),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => operations.push(Operation::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+// the json pointer of a key under a path
+fn child(path: &str, key: &str) -> String {
+    format!("{}/{}", path, escape(key))
+}
+
+// `~` and `/` in keys are escaped as RFC 6901 wants
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// the path does not start with `/` or has an index that is not a number
+    InvalidPath(String),
+    /// nothing to remove or replace at the path, or no parent to add to
+    NotFound(String),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::InvalidPath(path) => write!(f, "invalid path '{}'", path),
+            PatchError::NotFound(path) => write!(f, "nothing found at '{}'", path),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// applies RFC 6902 operations in order, stops at the first one that fails
+pub fn apply_json_patch(target: &mut Value, operations: &[Operation]) -> Result<(), PatchError> {
+    for operation in operations {
+        match operation {
+            Operation::Add { path, value } => add(target, path, value.clone())?,
+            Operation::Remove { path } => {
+                remove(target, path)?;
+            }
+            Operation::Replace { path, value } => {
+                let existing = target
+                    .pointer_mut(path)
+                    .ok_or_else(|| PatchError::NotFound(path.to_owned()))?;
+                *existing = value.clone();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+
+    let (parent, key) = split(path)?;
+
+    match target.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(key, value);
+        }
+        Some(Value::Array(items)) if key == "-" => items.push(value),
+        Some(Value::Array(items)) => {
+            let index = index(&key, path)?;
+
+            if index > items.len() {
+                return Err(PatchError::NotFound(path.to_owned()));
+            }
+
+            items.insert(index, value);
+        }
+        _ => return Err(PatchError::NotFound(path.to_owned())),
+    }
+
+    Ok(())
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let (parent, key) = split(path)?;
+
+    let removed = match target.pointer_mut(parent) {
+        Some(Value::Object(map)) => map.remove(&key),
+        Some(Value::Array(items)) => {
+            let index = index(&key, path)?;
+            (index < items.len()).then(|| items.remove(index))
+        }
+        _ => None,
+    };
+
+    removed.ok_or_else(|| PatchError::NotFound(path.to_owned()))
+}
+
+// the pointer of the parent and the unescaped last key
+fn split(path: &str) -> Result<(&str, String), PatchError> {
+    let (parent, key) = path
+        .rsplit_once('/')
+        .ok_or_else(|| PatchError::InvalidPath(path.to_owned()))?;
+
+    Ok((parent, key.replace("~1", "/").replace("~0", "~")))
+}
+
+fn index(key: &str, path: &str) -> Result<usize, PatchError> {
+    key.parse()
+        .map_err(|_| PatchError::InvalidPath(path.to_owned()))
+}
@@ -1,26 +1,282 @@
-use serde_json::Value;
+use serde_json::{Map, Value};
+
+/// the key the f1 feed retracts data with, `{"_deleted": ["44"]}` removes the key "44"
+/// (or index 44 of a list) before the rest of the update is merged
+pub const DELETED: &str = "_deleted";
+
+/// How the values at a path are merged with an update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// objects merge key by key, a new array is appended to the old one,
+    /// an object patches an array by index, what [`merge`] does everywhere
+    Append,
+    /// the update replaces the value as is, for topics that always send everything
+    Replace,
+    /// a new array patches the old one item by item instead of being appended
+    IndexPatch,
+    /// array items are matched by the value of this field, matches are merged and the rest appended
+    Keyed(&'static str),
+}
+
+/// Which [`Strategy`] to merge a path with, everything without a rule is [`Strategy::Append`].
+///
+/// Paths are `/` separated keys from the top of the state, `*` matches any key or index.
+/// Keys are compared without case and underscores, so one table works for the raw feed
+/// and for the transformed state alike.
+#[derive(Debug, Clone, Default)]
+pub struct Strategies {
+    rules: Vec<(Vec<String>, Strategy)>,
+}
+
+impl Strategies {
+    pub fn new() -> Self {
+        Strategies::default()
+    }
+
+    /// how the f1 feed sends its topics
+    pub fn feed() -> Self {
+        Strategies::new()
+            // a full batch of samples every time
+            .rule("carData", Strategy::Replace)
+            .rule("position", Strategy::Replace)
+            // new entries are sent on their own, the lists only ever grow
+            .rule("raceControlMessages/messages", Strategy::Append)
+            .rule("sessionData/series", Strategy::Append)
+            .rule("sessionData/statusSeries", Strategy::Append)
+            // every capture has its own recording, resent ones are the same capture
+            .rule("teamRadio/captures", Strategy::Keyed("path"))
+            // fixed length lists, sent whole in the initial and patched by index after
+            .rule("timingAppData/lines/*/stints", Strategy::IndexPatch)
+            .rule("timingData/lines/*/sectors", Strategy::IndexPatch)
+            .rule(
+                "timingData/lines/*/sectors/*/segments",
+                Strategy::IndexPatch,
+            )
+            .rule("timingStats/lines/*/bestSectors", Strategy::IndexPatch)
+            .rule("topThree/lines", Strategy::IndexPatch)
+    }
+
+    pub fn rule(mut self, path: &str, strategy: Strategy) -> Self {
+        let path = path
+            .split('/')
+            .filter(|key| !key.is_empty())
+            .map(normalize)
+            .collect();
+
+        self.rules.push((path, strategy));
+        self
+    }
+
+    /// the strategy for a path of normalized keys, the first matching rule wins
+    fn get(&self, path: &[String]) -> Strategy {
+        self.rules
+            .iter()
+            .find(|(rule, _)| {
+                rule.len() == path.len()
+                    && rule
+                        .iter()
+                        .zip(path)
+                        .all(|(rule, key)| rule == "*" || rule == key)
+            })
+            .map(|(_, strategy)| *strategy)
+            .unwrap_or(Strategy::Append)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
 
 pub fn merge(base: &mut Value, update: Value) {
     match (base, update) {
-        (Value::Object(ref mut prev), Value::Object(update)) => {
+        (Value::Object(ref mut prev), Value::Object(mut update)) => {
+            delete_keys(prev, &mut update);
+
             for (k, v) in update {
                 merge(prev.entry(k).or_insert(Value::Null), v);
             }
         }
         (Value::Array(ref mut a), Value::Array(b)) => {
-            a.extend(b);
+            a.extend(b.into_iter().map(without_deleted));
         }
-        (Value::Array(ref mut prev), Value::Object(update)) => {
+        (Value::Array(ref mut prev), Value::Object(mut update)) => {
+            delete_indexes(prev, &mut update);
+
             for (k, v) in update {
-                if let Some(index) = k.parse::<usize>().ok() {
+                if let Ok(index) = k.parse::<usize>() {
                     if let Some(item) = prev.get_mut(index) {
                         merge(item, v);
                     } else {
-                        prev.push(v);
+                        prev.push(without_deleted(v));
+                    }
+                }
+            }
+        }
+        (a, b) => *a = without_deleted(b),
+    }
+}
+
+/// like [`merge`] but every path is merged with its strategy from the table
+pub fn merge_with(base: &mut Value, update: Value, strategies: &Strategies) {
+    if strategies.is_empty() {
+        return merge(base, update);
+    }
+
+    merge_at(base, update, strategies, &mut Vec::new());
+}
+
+fn merge_at(base: &mut Value, update: Value, strategies: &Strategies, path: &mut Vec<String>) {
+    match (strategies.get(path), base, update) {
+        (Strategy::Replace, base, update) => *base = without_deleted(update),
+        (Strategy::IndexPatch, Value::Array(prev), Value::Array(update)) => {
+            for (index, item) in update.into_iter().enumerate() {
+                match prev.get_mut(index) {
+                    Some(prev) => merge_child(prev, item, index.to_string(), strategies, path),
+                    None => prev.push(without_deleted(item)),
+                }
+            }
+        }
+        (Strategy::Keyed(field), Value::Array(prev), Value::Array(update)) => {
+            for item in update {
+                let existing = field_value(&item, field)
+                    .and_then(|key| prev.iter().position(|p| field_value(p, field) == Some(key)));
+
+                match existing {
+                    Some(index) => {
+                        merge_child(&mut prev[index], item, index.to_string(), strategies, path)
+                    }
+                    None => prev.push(without_deleted(item)),
+                }
+            }
+        }
+        (_, Value::Object(prev), Value::Object(mut update)) => {
+            delete_keys(prev, &mut update);
+
+            for (k, v) in update {
+                let key = normalize(&k);
+                merge_child(
+                    prev.entry(k).or_insert(Value::Null),
+                    v,
+                    key,
+                    strategies,
+                    path,
+                );
+            }
+        }
+        (_, Value::Array(a), Value::Array(b)) => {
+            a.extend(b.into_iter().map(without_deleted));
+        }
+        (_, Value::Array(prev), Value::Object(mut update)) => {
+            delete_indexes(prev, &mut update);
+
+            for (k, v) in update {
+                if let Ok(index) = k.parse::<usize>() {
+                    match prev.get_mut(index) {
+                        Some(item) => merge_child(item, v, k, strategies, path),
+                        None => prev.push(without_deleted(v)),
                     }
                 }
             }
         }
-        (a, b) => *a = b,
+        (_, a, b) => *a = without_deleted(b),
+    }
+}
+
+fn merge_child(
+    base: &mut Value,
+    update: Value,
+    key: String,
+    strategies: &Strategies,
+    path: &mut Vec<String>,
+) {
+    path.push(key);
+    merge_at(base, update, strategies, path);
+    path.pop();
+}
+
+fn field_value<'a>(item: &'a Value, field: &str) -> Option<&'a Value> {
+    let field = normalize(field);
+
+    item.as_object()?
+        .iter()
+        .find(|(key, _)| normalize(key) == field)
+        .map(|(_, value)| value)
+}
+
+// "TimingAppData", "timingAppData" and "timing_app_data" are all "timingappdata"
+fn normalize(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn delete_keys(prev: &mut Map<String, Value>, update: &mut Map<String, Value>) {
+    if let Some(deleted) = update.remove(DELETED) {
+        for key in deleted_keys(&deleted) {
+            prev.remove(&key);
+        }
+    }
+}
+
+fn delete_indexes(prev: &mut Vec<Value>, update: &mut Map<String, Value>) {
+    let Some(deleted) = update.remove(DELETED) else {
+        return;
+    };
+
+    let mut indexes: Vec<usize> = deleted_keys(&deleted)
+        .iter()
+        .filter_map(|k| k.parse().ok())
+        .collect();
+
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    // from the back so the indexes still point at the right items
+    for index in indexes.into_iter().rev() {
+        if index < prev.len() {
+            prev.remove(index);
+        }
+    }
+}
+
+// the marker can be a list of keys or a single one, indexes are sometimes sent as numbers
+fn deleted_keys(deleted: &Value) -> Vec<String> {
+    match deleted {
+        Value::Array(keys) => keys.iter().filter_map(key).collect(),
+        other => key(other).into_iter().collect(),
+    }
+}
+
+fn key(value: &Value) -> Option<String> {
+    match value {
+        Value::String(key) => Some(key.to_owned()),
+        Value::Number(index) => Some(index.to_string()),
+        _ => None,
+    }
+}
+
+// there is nothing to delete in data we did not have yet, but the marker must not end up in the state
+fn without_deleted(mut value: Value) -> Value {
+    strip_deleted(&mut value);
+    value
+}
+
+pub(crate) fn strip_deleted(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove(DELETED);
+            map.values_mut().for_each(strip_deleted);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_deleted),
+        _ => {}
+    }
+}
+
+pub(crate) fn has_deleted(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.contains_key(DELETED) || map.values().any(has_deleted),
+        Value::Array(items) => items.iter().any(has_deleted),
+        _ => false,
     }
 }
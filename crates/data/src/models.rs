@@ -0,0 +1,253 @@
+//! Typed models for the topics we subscribe to.
+//!
+//! They describe the state after it went through the transformer, so keys are camelCase.
+//! Every field is optional, this way the same type can be used for the full state
+//! as well as for the partial updates the feed sends, lists use [`Indexed`] for the same reason.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::merge::{has_deleted, strip_deleted};
+
+mod car_data;
+mod championship_prediction;
+mod driver_list;
+mod extrapolated_clock;
+mod heartbeat;
+mod indexed;
+mod lap_count;
+mod pit_lane_time_collection;
+mod position;
+mod race_control_messages;
+mod session_data;
+mod session_info;
+mod team_radio;
+mod timing_app_data;
+mod timing_data;
+mod timing_stats;
+mod top_three;
+mod track_status;
+mod weather_data;
+
+pub use car_data::*;
+pub use championship_prediction::*;
+pub use driver_list::*;
+pub use extrapolated_clock::*;
+pub use heartbeat::*;
+pub use indexed::*;
+pub use lap_count::*;
+pub use pit_lane_time_collection::*;
+pub use position::*;
+pub use race_control_messages::*;
+pub use session_data::*;
+pub use session_info::*;
+pub use team_radio::*;
+pub use timing_app_data::*;
+pub use timing_data::*;
+pub use timing_stats::*;
+pub use top_three::*;
+pub use track_status::*;
+pub use weather_data::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Heartbeat,
+    CarData,
+    Position,
+    ExtrapolatedClock,
+    TopThree,
+    RcmSeries,
+    TimingStats,
+    TimingAppData,
+    WeatherData,
+    TrackStatus,
+    DriverList,
+    RaceControlMessages,
+    SessionInfo,
+    SessionData,
+    LapCount,
+    TimingData,
+    TeamRadio,
+    PitLaneTimeCollection,
+    ChampionshipPrediction,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 19] = [
+        Topic::Heartbeat,
+        Topic::CarData,
+        Topic::Position,
+        Topic::ExtrapolatedClock,
+        Topic::TopThree,
+        Topic::RcmSeries,
+        Topic::TimingStats,
+        Topic::TimingAppData,
+        Topic::WeatherData,
+        Topic::TrackStatus,
+        Topic::DriverList,
+        Topic::RaceControlMessages,
+        Topic::SessionInfo,
+        Topic::SessionData,
+        Topic::LapCount,
+        Topic::TimingData,
+        Topic::TeamRadio,
+        Topic::PitLaneTimeCollection,
+        Topic::ChampionshipPrediction,
+    ];
+
+    /// the name f1 uses, without the ".z" of compressed topics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::Heartbeat => "Heartbeat",
+            Topic::CarData => "CarData",
+            Topic::Position => "Position",
+            Topic::ExtrapolatedClock => "ExtrapolatedClock",
+            Topic::TopThree => "TopThree",
+            Topic::RcmSeries => "RcmSeries",
+            Topic::TimingStats => "TimingStats",
+            Topic::TimingAppData => "TimingAppData",
+            Topic::WeatherData => "WeatherData",
+            Topic::TrackStatus => "TrackStatus",
+            Topic::DriverList => "DriverList",
+            Topic::RaceControlMessages => "RaceControlMessages",
+            Topic::SessionInfo => "SessionInfo",
+            Topic::SessionData => "SessionData",
+            Topic::LapCount => "LapCount",
+            Topic::TimingData => "TimingData",
+            Topic::TeamRadio => "TeamRadio",
+            Topic::PitLaneTimeCollection => "PitLaneTimeCollection",
+            Topic::ChampionshipPrediction => "ChampionshipPrediction",
+        }
+    }
+
+    /// the key of the topic in the transformed state
+    pub fn key(&self) -> &'static str {
+        match self {
+            Topic::Heartbeat => "heartbeat",
+            Topic::CarData => "carData",
+            Topic::Position => "position",
+            Topic::ExtrapolatedClock => "extrapolatedClock",
+            Topic::TopThree => "topThree",
+            Topic::RcmSeries => "rcmSeries",
+            Topic::TimingStats => "timingStats",
+            Topic::TimingAppData => "timingAppData",
+            Topic::WeatherData => "weatherData",
+            Topic::TrackStatus => "trackStatus",
+            Topic::DriverList => "driverList",
+            Topic::RaceControlMessages => "raceControlMessages",
+            Topic::SessionInfo => "sessionInfo",
+            Topic::SessionData => "sessionData",
+            Topic::LapCount => "lapCount",
+            Topic::TimingData => "timingData",
+            Topic::TeamRadio => "teamRadio",
+            Topic::PitLaneTimeCollection => "pitLaneTimeCollection",
+            Topic::ChampionshipPrediction => "championshipPrediction",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Topic> {
+        let name = name.strip_suffix(".z").unwrap_or(name);
+        Topic::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    pub fn from_key(key: &str) -> Option<Topic> {
+        Topic::ALL.into_iter().find(|t| t.key() == key)
+    }
+}
+
+/// a single (partial) topic update, as found under one key of an update or the state
+#[derive(Debug, Clone, PartialEq)]
+pub enum Update {
+    Heartbeat(Heartbeat),
+    CarData(CarData),
+    Position(Position),
+    ExtrapolatedClock(ExtrapolatedClock),
+    TopThree(TopThree),
+    RcmSeries(Value),
+    TimingStats(TimingStats),
+    TimingAppData(TimingAppData),
+    WeatherData(WeatherData),
+    TrackStatus(TrackStatus),
+    DriverList(DriverList),
+    RaceControlMessages(RaceControlMessages),
+    SessionInfo(Box<SessionInfo>),
+    SessionData(SessionData),
+    LapCount(LapCount),
+    TimingData(TimingData),
+    TeamRadio(TeamRadio),
+    PitLaneTimeCollection(PitLaneTimeCollection),
+    ChampionshipPrediction(ChampionshipPrediction),
+    /// a topic we don't have a model for (yet)
+    Unknown(String, Value),
+}
+
+impl Update {
+    /// parses the data of the transformed `key`, `_deleted` markers are left out
+    pub fn parse(key: &str, data: &Value) -> Result<Update, serde_json::Error> {
+        let Some(topic) = Topic::from_key(key) else {
+            return Ok(Update::Unknown(key.to_owned(), data.clone()));
+        };
+
+        let update = match topic {
+            Topic::Heartbeat => Update::Heartbeat(parse(data)?),
+            Topic::CarData => Update::CarData(parse(data)?),
+            Topic::Position => Update::Position(parse(data)?),
+            Topic::ExtrapolatedClock => Update::ExtrapolatedClock(parse(data)?),
+            Topic::TopThree => Update::TopThree(parse(data)?),
+            Topic::RcmSeries => Update::RcmSeries(data.clone()),
+            Topic::TimingStats => Update::TimingStats(parse(data)?),
+            Topic::TimingAppData => Update::TimingAppData(parse(data)?),
+            Topic::WeatherData => Update::WeatherData(parse(data)?),
+            Topic::TrackStatus => Update::TrackStatus(parse(data)?),
+            Topic::DriverList => Update::DriverList(parse(data)?),
+            Topic::RaceControlMessages => Update::RaceControlMessages(parse(data)?),
+            Topic::SessionInfo => Update::SessionInfo(Box::new(parse(data)?)),
+            Topic::SessionData => Update::SessionData(parse(data)?),
+            Topic::LapCount => Update::LapCount(parse(data)?),
+            Topic::TimingData => Update::TimingData(parse(data)?),
+            Topic::TeamRadio => Update::TeamRadio(parse(data)?),
+            Topic::PitLaneTimeCollection => Update::PitLaneTimeCollection(parse(data)?),
+            Topic::ChampionshipPrediction => Update::ChampionshipPrediction(parse(data)?),
+        };
+
+        Ok(update)
+    }
+
+    pub fn topic(&self) -> Option<Topic> {
+        let topic = match self {
+            Update::Heartbeat(_) => Topic::Heartbeat,
+            Update::CarData(_) => Topic::CarData,
+            Update::Position(_) => Topic::Position,
+            Update::ExtrapolatedClock(_) => Topic::ExtrapolatedClock,
+            Update::TopThree(_) => Topic::TopThree,
+            Update::RcmSeries(_) => Topic::RcmSeries,
+            Update::TimingStats(_) => Topic::TimingStats,
+            Update::TimingAppData(_) => Topic::TimingAppData,
+            Update::WeatherData(_) => Topic::WeatherData,
+            Update::TrackStatus(_) => Topic::TrackStatus,
+            Update::DriverList(_) => Topic::DriverList,
+            Update::RaceControlMessages(_) => Topic::RaceControlMessages,
+            Update::SessionInfo(_) => Topic::SessionInfo,
+            Update::SessionData(_) => Topic::SessionData,
+            Update::LapCount(_) => Topic::LapCount,
+            Update::TimingData(_) => Topic::TimingData,
+            Update::TeamRadio(_) => Topic::TeamRadio,
+            Update::PitLaneTimeCollection(_) => Topic::PitLaneTimeCollection,
+            Update::ChampionshipPrediction(_) => Topic::ChampionshipPrediction,
+            Update::Unknown(..) => return None,
+        };
+
+        Some(topic)
+    }
+}
+
+// the models have no place for `_deleted`, what is retracted is only in the merged state
+pub(crate) fn parse<T: DeserializeOwned>(data: &Value) -> Result<T, serde_json::Error> {
+    if !has_deleted(data) {
+        return T::deserialize(data);
+    }
+
+    let mut data = data.clone();
+    strip_deleted(&mut data);
+    serde_json::from_value(data)
+}
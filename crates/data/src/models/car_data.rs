@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CarData {
+    pub entries: Option<Indexed<CarDataEntry>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CarDataEntry {
+    pub utc: Option<String>,
+    /// keyed by racing number
+    pub cars: Option<BTreeMap<String, Car>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Car {
+    /// raw channels keyed by their numeric id
+    pub channels: Option<BTreeMap<String, i64>>,
+}
+
+/// The channels of a [`Car`] we know the meaning of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Rpm,
+    Speed,
+    Gear,
+    Throttle,
+    Brake,
+    Drs,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 6] = [
+        Channel::Rpm,
+        Channel::Speed,
+        Channel::Gear,
+        Channel::Throttle,
+        Channel::Brake,
+        Channel::Drs,
+    ];
+
+    /// the key of the channel in [`Car::channels`]
+    pub fn id(&self) -> &'static str {
+        match self {
+            Channel::Rpm => "0",
+            Channel::Speed => "2",
+            Channel::Gear => "3",
+            Channel::Throttle => "4",
+            Channel::Brake => "5",
+            Channel::Drs => "45",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Channel> {
+        Channel::ALL.into_iter().find(|c| c.id() == id)
+    }
+}
+
+/// The state of the rear wing flap.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Drs {
+    Off,
+    /// within a second in a detection zone, can open in the next activation zone
+    Eligible,
+    Open,
+}
+
+impl Drs {
+    /// 0 and 1 are off, 8 is eligible and 10, 12 and 14 are open
+    pub fn from_channel(value: i64) -> Drs {
+        match value {
+            10 | 12 | 14 => Drs::Open,
+            8 => Drs::Eligible,
+            _ => Drs::Off,
+        }
+    }
+}
+
+/// A sample of a car with named channels.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Telemetry {
+    /// when the sample was taken, the utc of its entry
+    pub utc: Option<String>,
+    pub rpm: Option<i64>,
+    /// in km/h
+    pub speed: Option<i64>,
+    /// 0 is neutral
+    pub gear: Option<i64>,
+    /// in percent, can go slightly over 100
+    pub throttle: Option<i64>,
+    pub brake: Option<bool>,
+    pub drs: Option<Drs>,
+}
+
+impl Car {
+    pub fn channel(&self, channel: Channel) -> Option<i64> {
+        self.channels.as_ref()?.get(channel.id()).copied()
+    }
+
+    pub fn telemetry(&self, utc: Option<&str>) -> Telemetry {
+        Telemetry {
+            utc: utc.map(str::to_owned),
+            rpm: self.channel(Channel::Rpm),
+            speed: self.channel(Channel::Speed),
+            gear: self.channel(Channel::Gear),
+            throttle: self.channel(Channel::Throttle),
+            // 0 or 100, some older sessions send 1
+            brake: self.channel(Channel::Brake).map(|brake| brake > 0),
+            drs: self.channel(Channel::Drs).map(Drs::from_channel),
+        }
+    }
+}
+
+impl CarDataEntry {
+    /// the sample of every car in the entry, keyed by racing number
+    pub fn telemetry(&self) -> impl Iterator<Item = (&String, Telemetry)> {
+        self.cars
+            .iter()
+            .flatten()
+            .map(|(racing_number, car)| (racing_number, car.telemetry(self.utc.as_deref())))
+    }
+}
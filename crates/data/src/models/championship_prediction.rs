@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionshipPrediction {
+    pub drivers: Option<BTreeMap<String, ChampionshipDriver>>,
+    pub teams: Option<BTreeMap<String, ChampionshipTeam>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionshipDriver {
+    pub racing_number: Option<String>,
+    pub current_position: Option<i64>,
+    pub predicted_position: Option<i64>,
+    pub current_points: Option<f64>,
+    pub predicted_points: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionshipTeam {
+    pub team_name: Option<String>,
+    pub current_position: Option<i64>,
+    pub predicted_position: Option<i64>,
+    pub current_points: Option<f64>,
+    pub predicted_points: Option<f64>,
+}
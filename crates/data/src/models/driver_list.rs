@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// keyed by racing number
+pub type DriverList = BTreeMap<String, Driver>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub racing_number: Option<String>,
+    pub broadcast_name: Option<String>,
+    pub full_name: Option<String>,
+    pub tla: Option<String>,
+    pub line: Option<i64>,
+    pub team_name: Option<String>,
+    pub team_colour: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub reference: Option<String>,
+    pub headshot_url: Option<String>,
+    pub country_code: Option<String>,
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrapolatedClock {
+    pub utc: Option<String>,
+    pub remaining: Option<String>,
+    pub extrapolating: Option<bool>,
+}
@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A list as the f1 feed sends it, a full array in the initial
+/// and an object keyed by the changed indexes (`{"3": {...}}`) in updates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Indexed<T>(pub BTreeMap<usize, T>);
+
+impl<T> Default for Indexed<T> {
+    fn default() -> Self {
+        Indexed(BTreeMap::new())
+    }
+}
+
+impl<T> Indexed<T> {
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.0.get(&index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&usize, &T)> {
+        self.0.iter()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.0.values()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.0.values().next_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// true when the indexes are `0..len`, so this is a full list and not a partial update
+    pub fn is_complete(&self) -> bool {
+        self.0.keys().enumerate().all(|(i, k)| i == *k)
+    }
+}
+
+impl<T> From<Vec<T>> for Indexed<T> {
+    fn from(list: Vec<T>) -> Self {
+        Indexed(list.into_iter().enumerate().collect())
+    }
+}
+
+impl<T: Serialize> Serialize for Indexed<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_complete() {
+            let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+            for item in self.0.values() {
+                seq.serialize_element(item)?;
+            }
+            return seq.end();
+        }
+
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (index, item) in &self.0 {
+            map.serialize_entry(&index.to_string(), item)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Indexed<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(IndexedVisitor(PhantomData))
+    }
+}
+
+struct IndexedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for IndexedVisitor<T> {
+    type Value = Indexed<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array or an object keyed by array indexes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = BTreeMap::new();
+        while let Some(item) = seq.next_element()? {
+            items.insert(items.len(), item);
+        }
+        Ok(Indexed(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut items = BTreeMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.parse::<usize>() {
+                Ok(index) => {
+                    items.insert(index, map.next_value()?);
+                }
+                // markers like `_deleted` are not list items
+                Err(_) => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Indexed(items))
+    }
+}
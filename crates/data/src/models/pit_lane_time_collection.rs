@@ -0,0 +1,17 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PitLaneTimeCollection {
+    pub pit_times: Option<BTreeMap<String, PitLaneTime>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PitLaneTime {
+    pub racing_number: Option<String>,
+    pub duration: Option<String>,
+    pub lap: Option<String>,
+}
@@ -0,0 +1,28 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub position: Option<Indexed<PositionEntry>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionEntry {
+    pub timestamp: Option<String>,
+    /// keyed by racing number
+    pub entries: Option<BTreeMap<String, PositionCar>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionCar {
+    pub status: Option<String>,
+    pub x: Option<i64>,
+    pub y: Option<i64>,
+    pub z: Option<i64>,
+}
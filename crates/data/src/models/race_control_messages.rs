@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaceControlMessages {
+    pub messages: Option<Indexed<RaceControlMessage>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaceControlMessage {
+    pub utc: Option<String>,
+    pub lap: Option<i64>,
+    pub category: Option<String>,
+    pub message: Option<String>,
+    pub flag: Option<String>,
+    pub scope: Option<String>,
+    pub sector: Option<i64>,
+    pub racing_number: Option<String>,
+    pub status: Option<String>,
+    pub mode: Option<String>,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionData {
+    pub series: Option<Indexed<Series>>,
+    pub status_series: Option<Indexed<StatusSeries>>,
+}
+
+/// either a new lap (races) or a new qualifying part
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Series {
+    pub utc: Option<String>,
+    pub lap: Option<i64>,
+    pub qualifying_part: Option<i64>,
+}
+
+/// either a track status or a session status change
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusSeries {
+    pub utc: Option<String>,
+    pub track_status: Option<String>,
+    pub session_status: Option<String>,
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub meeting: Option<Meeting>,
+    pub archive_status: Option<ArchiveStatus>,
+    pub key: Option<i64>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub name: Option<String>,
+    pub number: Option<i64>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub gmt_offset: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Meeting {
+    pub key: Option<i64>,
+    pub name: Option<String>,
+    pub official_name: Option<String>,
+    pub location: Option<String>,
+    pub country: Option<Country>,
+    pub circuit: Option<Circuit>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Country {
+    pub key: Option<i64>,
+    pub code: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Circuit {
+    pub key: Option<i64>,
+    pub short_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStatus {
+    pub status: Option<String>,
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamRadio {
+    pub captures: Option<Indexed<RadioCapture>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RadioCapture {
+    pub utc: Option<String>,
+    pub racing_number: Option<String>,
+    /// relative to the session path
+    pub path: Option<String>,
+}
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingAppData {
+    pub lines: Option<BTreeMap<String, TimingAppDataDriver>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingAppDataDriver {
+    pub racing_number: Option<String>,
+    pub line: Option<i64>,
+    pub grid_pos: Option<String>,
+    pub stints: Option<Indexed<Stint>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Stint {
+    pub lap_flags: Option<i64>,
+    pub compound: Option<String>,
+    /// "true" or "false", the feed sends it as a string
+    pub new: Option<String>,
+    pub tyres_not_changed: Option<String>,
+    pub total_laps: Option<i64>,
+    pub start_laps: Option<i64>,
+    pub lap_time: Option<String>,
+    pub lap_number: Option<i64>,
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingData {
+    pub lines: Option<BTreeMap<String, TimingDataDriver>>,
+    pub withheld: Option<bool>,
+    pub session_part: Option<i64>,
+    pub cut_off_time: Option<String>,
+    pub cut_off_percentage: Option<String>,
+    pub no_entries: Option<Indexed<i64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingDataDriver {
+    pub racing_number: Option<String>,
+    pub line: Option<i64>,
+    pub position: Option<String>,
+    pub show_position: Option<bool>,
+    pub gap_to_leader: Option<String>,
+    pub interval_to_position_ahead: Option<Interval>,
+    pub time_diff_to_fastest: Option<String>,
+    pub time_diff_to_position_ahead: Option<String>,
+    pub stats: Option<Indexed<Stats>>,
+    pub retired: Option<bool>,
+    pub in_pit: Option<bool>,
+    pub pit_out: Option<bool>,
+    pub stopped: Option<bool>,
+    pub status: Option<i64>,
+    pub number_of_laps: Option<i64>,
+    pub number_of_pit_stops: Option<i64>,
+    pub sectors: Option<Indexed<Sector>>,
+    pub speeds: Option<Speeds>,
+    pub best_lap_time: Option<BestLapTime>,
+    pub best_lap_times: Option<Indexed<BestLapTime>>,
+    pub last_lap_time: Option<LapTime>,
+    pub knocked_out: Option<bool>,
+    pub cutoff: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Interval {
+    pub value: Option<String>,
+    pub catching: Option<bool>,
+}
+
+/// qualifying only, one entry per session part
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub time_diff_to_fastest: Option<String>,
+    // the feed really spells it like this
+    #[serde(rename = "timeDifftoPositionAhead")]
+    pub time_diff_to_position_ahead: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Sector {
+    pub stopped: Option<bool>,
+    pub value: Option<String>,
+    pub previous_value: Option<String>,
+    pub status: Option<i64>,
+    pub overall_fastest: Option<bool>,
+    pub personal_fastest: Option<bool>,
+    pub segments: Option<Indexed<Segment>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    pub status: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Speeds {
+    pub i1: Option<LapTime>,
+    pub i2: Option<LapTime>,
+    pub fl: Option<LapTime>,
+    pub st: Option<LapTime>,
+}
+
+/// used for the last lap time as well as the speed traps
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LapTime {
+    pub value: Option<String>,
+    pub status: Option<i64>,
+    pub overall_fastest: Option<bool>,
+    pub personal_fastest: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BestLapTime {
+    pub value: Option<String>,
+    pub lap: Option<i64>,
+}
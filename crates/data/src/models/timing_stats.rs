@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingStats {
+    pub withheld: Option<bool>,
+    pub lines: Option<BTreeMap<String, TimingStatsDriver>>,
+    pub session_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingStatsDriver {
+    pub line: Option<i64>,
+    pub racing_number: Option<String>,
+    pub personal_best_lap_time: Option<PersonalBest>,
+    pub best_sectors: Option<Indexed<PersonalBest>>,
+    pub best_speeds: Option<BestSpeeds>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalBest {
+    pub value: Option<String>,
+    pub position: Option<i64>,
+    pub lap: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BestSpeeds {
+    pub i1: Option<PersonalBest>,
+    pub i2: Option<PersonalBest>,
+    pub fl: Option<PersonalBest>,
+    pub st: Option<PersonalBest>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use super::Indexed;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TopThree {
+    pub withheld: Option<bool>,
+    pub lines: Option<Indexed<TopThreeDriver>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TopThreeDriver {
+    pub position: Option<String>,
+    pub show_position: Option<bool>,
+    pub racing_number: Option<String>,
+    pub tla: Option<String>,
+    pub broadcast_name: Option<String>,
+    pub full_name: Option<String>,
+    pub team: Option<String>,
+    pub team_colour: Option<String>,
+    pub lap_time: Option<String>,
+    pub lap_state: Option<i64>,
+    pub diff_to_ahead: Option<String>,
+    pub diff_to_leader: Option<String>,
+    pub overall_fastest: Option<bool>,
+    pub personal_fastest: Option<bool>,
+}
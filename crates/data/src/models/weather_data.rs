@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// all values are sent as strings by the feed
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherData {
+    pub air_temp: Option<String>,
+    pub humidity: Option<String>,
+    pub pressure: Option<String>,
+    pub rainfall: Option<String>,
+    pub track_temp: Option<String>,
+    pub wind_direction: Option<String>,
+    pub wind_speed: Option<String>,
+}
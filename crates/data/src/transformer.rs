@@ -1,44 +1,260 @@
-use std::mem;
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{LazyLock, RwLock},
+};
 
 use serde_json::{Map, Value};
 
-fn to_camel_case(string: &str) -> String {
-    heck::AsLowerCamelCase(string).to_string()
+use crate::merge::DELETED;
+
+/// the flag f1 marks keyframes with, it means nothing once the data is merged
+const KEYFRAME: &str = "_kf";
+
+/// The casing keys are renamed to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Casing {
+    /// the names as f1 sends them, mostly PascalCase
+    Original,
+    /// what the dashboard expects, `RacingNumber` becomes `racingNumber`
+    #[default]
+    CamelCase,
+    /// `RacingNumber` becomes `racing_number`
+    SnakeCase,
 }
 
-pub fn transform(value: &mut Value) {
+impl Casing {
+    /// "original", "camelCase" or "snake_case"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "original" => Some(Casing::Original),
+            "camelCase" => Some(Casing::CamelCase),
+            "snake_case" => Some(Casing::SnakeCase),
+            _ => None,
+        }
+    }
+
+    pub fn convert(&self, key: &str) -> String {
+        match self {
+            Casing::Original => key.to_owned(),
+            Casing::CamelCase => heck::AsLowerCamelCase(key).to_string(),
+            Casing::SnakeCase => heck::AsSnakeCase(key).to_string(),
+        }
+    }
+}
+
+/// Renames the keys of feed data to a [`Casing`].
+///
+/// F1 sends the same few hundred keys over and over, so every key is converted once
+/// and cached. Values are rewritten in place, only maps with keys that change are rebuilt.
+///
+/// Renaming loses information, `ST` and `St` both become `st`, so the transformer
+/// remembers the original of every key it saw. [`Transformer::inverse`] uses that
+/// to give the data back its f1 names, keys it never saw fall back to PascalCase.
+///
+/// Whatever reads the transformed data relies on its casing, so a different casing for
+/// consumers is set with [`Transformer::output_casing`] and applied on the way out.
+#[derive(Debug, Default)]
+pub struct Transformer {
+    casing: Casing,
+    output: Option<Casing>,
+    keep_keyframes: bool,
+    keys: RwLock<Keys>,
+}
+
+#[derive(Debug, Default)]
+struct Keys {
+    /// original key to converted key
+    converted: HashMap<String, String>,
+    /// converted key to the original it was first seen as
+    originals: HashMap<String, String>,
+}
+
+impl Transformer {
+    pub fn new(casing: Casing) -> Self {
+        Transformer {
+            casing,
+            ..Transformer::default()
+        }
+    }
+
+    /// keeps the `_kf` flag instead of dropping it
+    pub fn keep_keyframes(mut self, keep: bool) -> Self {
+        self.keep_keyframes = keep;
+        self
+    }
+
+    /// the casing [`Transformer::output`] renames to, the one of the transform by default
+    pub fn output_casing(mut self, casing: Casing) -> Self {
+        self.output = Some(casing);
+        self
+    }
+
+    pub fn casing(&self) -> Casing {
+        self.casing
+    }
+
+    pub fn transform(&self, value: &mut Value) {
+        let mut missed = HashMap::new();
+
+        {
+            let keys = self.keys.read().unwrap();
+
+            rename(
+                value,
+                &mut |key| {
+                    let converted = match keys.converted.get(key).or_else(|| missed.get(key)) {
+                        Some(converted) => converted,
+                        None => missed
+                            .entry(key.to_owned())
+                            .or_insert_with(|| self.casing.convert(key)),
+                    };
+
+                    (converted != key).then(|| converted.to_owned())
+                },
+                self.keep_keyframes,
+            );
+        }
+
+        // only new keys take the write lock, after the first few updates that is next to never
+        if !missed.is_empty() {
+            let mut keys = self.keys.write().unwrap();
+
+            for (key, converted) in missed {
+                keys.originals
+                    .entry(converted.clone())
+                    .or_insert_with(|| key.clone());
+                keys.converted.insert(key, converted);
+            }
+        }
+    }
+
+    /// transforms an update map like `{ "TimingData": { .. } }` into a value
+    pub fn transform_map(&self, map: &mut Map<String, Value>) -> Value {
+        let mut value = Value::Object(mem::take(map));
+        self.transform(&mut value);
+        value
+    }
+
+    /// renames transformed keys back to what f1 called them
+    pub fn inverse(&self, value: &mut Value) {
+        self.recase(value, Casing::Original);
+    }
+
+    /// renames transformed keys to the output casing, for what leaves the process
+    pub fn output(&self, value: &mut Value) {
+        self.recase(value, self.output.unwrap_or(self.casing));
+    }
+
+    /// renames transformed keys to another casing by way of their f1 names
+    pub fn recase(&self, value: &mut Value, casing: Casing) {
+        if casing == self.casing {
+            return;
+        }
+
+        let keys = self.keys.read().unwrap();
+
+        rename(
+            value,
+            &mut |key| {
+                let original = match (self.casing, keys.originals.get(key)) {
+                    (Casing::Original, _) => key.to_owned(),
+                    (_, Some(original)) => original.to_owned(),
+                    (_, None) => heck::AsUpperCamelCase(key).to_string(),
+                };
+
+                let renamed = casing.convert(&original);
+                (renamed != key).then_some(renamed)
+            },
+            true,
+        );
+    }
+}
+
+/// Renames every key of `value`, `rename_key` returns `None` for keys that stay.
+///
+/// Driver numbers and channels never change, so most maps
+/// of an update are left as they are and only walked.
+fn rename(
+    value: &mut Value,
+    rename_key: &mut dyn FnMut(&str) -> Option<String>,
+    keep_keyframes: bool,
+) {
     match value {
         Value::Object(object) => {
-            let mut camel_case_map = Map::new();
+            if !keep_keyframes {
+                object.remove(KEYFRAME);
+            }
+
+            let mut renames = Vec::with_capacity(object.len());
 
             for (key, value) in object.iter_mut() {
-                if key == "_kf" {
+                if key == KEYFRAME {
+                    renames.push(None);
+                    continue;
+                }
+
+                // the marker has to survive for merge, the keys it names are renamed like the rest
+                if key == DELETED {
+                    deleted_keys(value, rename_key);
+                    renames.push(None);
                     continue;
                 }
 
-                transform(value);
-                camel_case_map.insert(to_camel_case(&key), mem::take(value));
+                rename(value, rename_key, keep_keyframes);
+
+                renames.push(match is_number(key) {
+                    true => None,
+                    false => rename_key(key),
+                });
             }
 
-            *value = Value::Object(camel_case_map);
+            // most maps keep their keys, only the others are rebuilt
+            if renames.iter().any(Option::is_some) {
+                *object = mem::take(object)
+                    .into_iter()
+                    .zip(renames)
+                    .map(|((key, value), renamed)| (renamed.unwrap_or(key), value))
+                    .collect();
+            }
         }
         Value::Array(array) => {
             for value in array.iter_mut() {
-                transform(value);
+                rename(value, rename_key, keep_keyframes);
             }
         }
         _ => {}
     }
 }
 
-pub fn transform_map(map: &mut Map<String, Value>) -> Value {
-    let mut camel_case_map = Map::new();
-
-    for (key, value) in map.iter_mut() {
-        transform(value);
-        let new_key = to_camel_case(&key);
-        camel_case_map.insert(new_key, mem::take(value));
+fn deleted_keys(deleted: &mut Value, rename_key: &mut dyn FnMut(&str) -> Option<String>) {
+    match deleted {
+        Value::Array(keys) => {
+            for key in keys.iter_mut() {
+                deleted_keys(key, rename_key);
+            }
+        }
+        Value::String(key) => {
+            if let Some(renamed) = rename_key(key) {
+                *key = renamed;
+            }
+        }
+        _ => {}
     }
+}
+
+// no casing changes a number
+fn is_number(key: &str) -> bool {
+    !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit())
+}
+
+static CAMEL_CASE: LazyLock<Transformer> = LazyLock::new(|| Transformer::new(Casing::CamelCase));
 
-    Value::Object(camel_case_map)
+/// camelCases every key and drops `_kf`, what the dashboard expects
+pub fn transform(value: &mut Value) {
+    CAMEL_CASE.transform(value);
+}
+
+pub fn transform_map(map: &mut Map<String, Value>) -> Value {
+    CAMEL_CASE.transform_map(map)
 }
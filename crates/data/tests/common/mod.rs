@@ -0,0 +1,24 @@
+// every test binary uses its own few of these
+#![allow(dead_code)]
+
+use data::{analysis::Analysis, transformer};
+use serde_json::Value;
+
+/// feed data with the keys live gives it, f1 sends them PascalCase
+pub fn transformed(value: Value) -> Value {
+    let mut value = value;
+    transformer::transform(&mut value);
+    value
+}
+
+/// an analysis started from an initial state as f1 sends it
+pub fn initial(initial: Value) -> Analysis {
+    let mut analysis = Analysis::default();
+    analysis.initial(&transformed(initial)).unwrap();
+    analysis
+}
+
+/// an update as f1 sends it, `{ <topic>: <data> }`
+pub fn update(analysis: &mut Analysis, update: Value, timestamp: Option<&str>) {
+    analysis.update(&transformed(update), timestamp).unwrap();
+}
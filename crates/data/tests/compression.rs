@@ -0,0 +1,48 @@
+use data::compression::{deflate, inflate, Error};
+use serde_json::json;
+
+#[test]
+fn round_trips() {
+    let state = json!({
+        "Entries": [{ "Utc": "2024-03-02T15:04:05.123Z", "Cars": { "1": { "Channels": { "0": 11032, "2": 287 } } } }]
+    });
+
+    let deflated = deflate(state.to_string()).unwrap();
+
+    assert_ne!(deflated, state.to_string());
+    assert_eq!(inflate(&deflated).unwrap(), state);
+}
+
+// what the feed sends for CarData.z, raw deflate without a zlib header
+#[test]
+fn inflates_feed_data() {
+    let deflated = "q1YKyC/OLMnMz1Oyiq5WCsnMTS0uScwtULJSMjIwMtE1MNY1MAoxNLUyMLEyMNUzNDKOUqqNrQUA";
+
+    assert_eq!(
+        inflate(deflated).unwrap(),
+        json!({ "Position": [{ "Timestamp": "2024-03-02T15:04:05.123Z" }] })
+    );
+}
+
+#[test]
+fn invalid_base64() {
+    assert!(matches!(inflate("not base64!"), Err(Error::Base64(_))));
+}
+
+#[test]
+fn inflated_data_is_not_json() {
+    let deflated = deflate("not json".to_owned()).unwrap();
+
+    let error = inflate(&deflated).unwrap_err();
+
+    assert!(matches!(error, Error::Json(_)));
+    assert!(error
+        .to_string()
+        .starts_with("inflated data is not valid json"));
+}
+
+#[test]
+fn not_deflated() {
+    // "{}" in base64, the json is fine but was never deflated
+    assert!(matches!(inflate("e30="), Err(Error::Io(_))));
+}
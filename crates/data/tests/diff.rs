@@ -0,0 +1,160 @@
+use data::{
+    diff::{apply_json_patch, apply_merge_patch, json_patch, merge_patch, Operation, PatchError},
+    merge::merge,
+};
+use serde_json::{json, Value};
+
+fn before() -> Value {
+    json!({
+        "lapCount": { "currentLap": 41, "totalLaps": 57 },
+        "timingData": {
+            "lines": {
+                "1": { "gapToLeader": "", "numberOfLaps": 40, "inPit": false, "sectors": [{ "value": "28.910" }, { "value": "39.104" }, { "value": "" }] },
+                "16": { "gapToLeader": "+5.422", "numberOfLaps": 40, "inPit": false, "sectors": [{ "value": "29.022" }, { "value": "" }, { "value": "" }] }
+            }
+        },
+        "raceControlMessages": { "messages": [{ "category": "Flag", "flag": "GREEN" }] }
+    })
+}
+
+fn after() -> Value {
+    let mut state = before();
+
+    merge(
+        &mut state,
+        json!({
+            "lapCount": { "currentLap": 42 },
+            "timingData": { "lines": { "16": { "gapToLeader": "+5.108", "inPit": true, "sectors": { "1": { "value": "39.340" } } } } },
+            "raceControlMessages": { "messages": [{ "category": "Drs", "status": "ENABLED" }] }
+        }),
+    );
+
+    state
+}
+
+#[test]
+fn merge_patch_only_has_what_changed() {
+    let patch = merge_patch(&before(), &after());
+
+    assert_eq!(
+        patch,
+        json!({
+            "lapCount": { "currentLap": 42 },
+            "timingData": { "lines": { "16": {
+                "gapToLeader": "+5.108",
+                "inPit": true,
+                "sectors": [{ "value": "29.022" }, { "value": "39.340" }, { "value": "" }]
+            } } },
+            "raceControlMessages": { "messages": [{ "category": "Flag", "flag": "GREEN" }, { "category": "Drs", "status": "ENABLED" }] }
+        })
+    );
+
+    let mut state = before();
+    apply_merge_patch(&mut state, &patch);
+    assert_eq!(state, after());
+}
+
+#[test]
+fn merge_patch_removes_with_null() {
+    let from = json!({ "lines": { "1": { "inPit": true }, "44": { "inPit": false } } });
+    let to = json!({ "lines": { "1": { "inPit": true } } });
+
+    let patch = merge_patch(&from, &to);
+    assert_eq!(patch, json!({ "lines": { "44": null } }));
+
+    let mut state = from.clone();
+    apply_merge_patch(&mut state, &patch);
+    assert_eq!(state, to);
+
+    assert_eq!(merge_patch(&to, &to), json!({}));
+}
+
+#[test]
+fn json_patch_lists_every_operation() {
+    let operations = json_patch(&before(), &after());
+
+    assert_eq!(
+        operations,
+        [
+            Operation::Replace {
+                path: "/lapCount/currentLap".to_owned(),
+                value: json!(42)
+            },
+            Operation::Add {
+                path: "/raceControlMessages/messages/1".to_owned(),
+                value: json!({ "category": "Drs", "status": "ENABLED" })
+            },
+            Operation::Replace {
+                path: "/timingData/lines/16/gapToLeader".to_owned(),
+                value: json!("+5.108")
+            },
+            Operation::Replace {
+                path: "/timingData/lines/16/inPit".to_owned(),
+                value: json!(true)
+            },
+            Operation::Replace {
+                path: "/timingData/lines/16/sectors/1/value".to_owned(),
+                value: json!("39.340")
+            },
+        ]
+    );
+
+    let mut state = before();
+    apply_json_patch(&mut state, &operations).unwrap();
+    assert_eq!(state, after());
+}
+
+#[test]
+fn json_patch_round_trips_both_ways() {
+    let operations = json_patch(&after(), &before());
+
+    assert!(operations.contains(&Operation::Remove {
+        path: "/raceControlMessages/messages/1".to_owned()
+    }));
+
+    let mut state = after();
+    apply_json_patch(&mut state, &operations).unwrap();
+    assert_eq!(state, before());
+
+    let from = json!({ "series": [1, 2, 3, 4], "gone": { "a": 1 } });
+    let to = json!({ "series": [1, 5], "new/key": "~" });
+
+    let mut state = from.clone();
+    apply_json_patch(&mut state, &json_patch(&from, &to)).unwrap();
+    assert_eq!(state, to);
+}
+
+#[test]
+fn json_patch_is_rfc_6902_json() {
+    let from = json!({ "a/b": 1, "c~d": 2 });
+    let to = json!({ "c~d": 3 });
+
+    let operations = serde_json::to_value(json_patch(&from, &to)).unwrap();
+
+    assert_eq!(
+        operations,
+        json!([
+            { "op": "remove", "path": "/a~1b" },
+            { "op": "replace", "path": "/c~0d", "value": 3 }
+        ])
+    );
+
+    let parsed: Vec<Operation> = serde_json::from_value(operations).unwrap();
+    let mut state = from.clone();
+    apply_json_patch(&mut state, &parsed).unwrap();
+    assert_eq!(state, to);
+}
+
+#[test]
+fn json_patch_fails_on_missing_paths() {
+    let mut state = json!({ "lines": {} });
+
+    let result = apply_json_patch(
+        &mut state,
+        &[Operation::Remove {
+            path: "/lines/44".to_owned(),
+        }],
+    );
+
+    assert_eq!(result, Err(PatchError::NotFound("/lines/44".to_owned())));
+}
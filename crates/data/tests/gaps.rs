@@ -0,0 +1,201 @@
+mod common;
+
+use data::analysis::{gaps::DrsTrain, Analysis};
+use serde_json::{json, Value};
+
+fn timing_data() -> Value {
+    json!({
+        "TimingData": {
+            "Lines": {
+                "1": { "Position": "1", "NumberOfLaps": 3, "GapToLeader": "LAP 4", "IntervalToPositionAhead": { "Value": "LAP 4", "Catching": false } },
+                "11": { "Position": "2", "NumberOfLaps": 3, "GapToLeader": "+3.100", "IntervalToPositionAhead": { "Value": "+3.100", "Catching": false } },
+                "16": { "Position": "3", "NumberOfLaps": 3, "GapToLeader": "+3.900", "IntervalToPositionAhead": { "Value": "+0.800", "Catching": false } },
+                "55": { "Position": "4", "NumberOfLaps": 3, "GapToLeader": "+4.500", "IntervalToPositionAhead": { "Value": "+0.600", "Catching": true } },
+                "44": { "Position": "5", "NumberOfLaps": 3, "GapToLeader": "+9.000", "IntervalToPositionAhead": { "Value": "+4.500", "Catching": false } }
+            }
+        }
+    })
+}
+
+fn update(analysis: &mut Analysis, racing_number: &str, line: Value, timestamp: &str) {
+    let update = json!({ "TimingData": { "Lines": { racing_number: line } } });
+    common::update(analysis, update, Some(timestamp));
+}
+
+fn cross_line(analysis: &mut Analysis, racing_number: &str, number: i64) {
+    update(
+        analysis,
+        racing_number,
+        json!({ "NumberOfLaps": number }),
+        "2024-03-02T15:11:00.000Z",
+    );
+}
+
+#[test]
+fn samples_at_every_sector() {
+    let mut analysis = common::initial(timing_data());
+
+    update(
+        &mut analysis,
+        "16",
+        json!({ "GapToLeader": "+3.700", "IntervalToPositionAhead": { "Value": "+0.500", "Catching": true }, "Sectors": { "0": { "Value": "29.511" } } }),
+        "2024-03-02T15:10:00.000Z",
+    );
+    // sent again with a keyframe
+    update(
+        &mut analysis,
+        "16",
+        json!({ "Sectors": { "0": { "Value": "29.511" } } }),
+        "2024-03-02T15:10:01.000Z",
+    );
+    update(
+        &mut analysis,
+        "16",
+        json!({ "GapToLeader": "+3.600", "Sectors": { "1": { "Value": "39.180" } } }),
+        "2024-03-02T15:10:30.000Z",
+    );
+    update(
+        &mut analysis,
+        "16",
+        json!({ "GapToLeader": "+3.550", "IntervalToPositionAhead": { "Value": "+0.450" }, "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:00.000Z",
+    );
+    // the last sector time comes after the lap count
+    update(
+        &mut analysis,
+        "16",
+        json!({ "Sectors": { "2": { "Value": "27.410" } } }),
+        "2024-03-02T15:11:00.200Z",
+    );
+
+    let samples = analysis.gaps.driver("16").unwrap();
+
+    let sampled: Vec<_> = samples
+        .iter()
+        .map(|sample| {
+            (
+                sample.lap,
+                sample.sector,
+                sample.gap_to_leader.as_deref(),
+                sample.interval.as_deref(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        sampled,
+        [
+            (4, 1, Some("+3.700"), Some("+0.500")),
+            (4, 2, Some("+3.600"), Some("+0.500")),
+            (4, 3, Some("+3.550"), Some("+0.450")),
+        ]
+    );
+
+    assert_eq!(samples[2].interval_seconds(), Some(0.45));
+    assert_eq!(samples[2].catching, Some(true));
+    assert_eq!(samples[2].position, Some(3));
+    assert_eq!(samples[2].utc.as_deref(), Some("2024-03-02T15:11:00.000Z"));
+}
+
+#[test]
+fn leader_and_lapped_cars_have_no_seconds() {
+    let mut analysis = common::initial(timing_data());
+
+    cross_line(&mut analysis, "1", 4);
+    update(
+        &mut analysis,
+        "44",
+        json!({ "GapToLeader": "1L", "IntervalToPositionAhead": { "Value": "+52.100" }, "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:10.000Z",
+    );
+
+    let leader = &analysis.gaps.driver("1").unwrap()[0];
+    assert_eq!(leader.gap_to_leader.as_deref(), Some("LAP 4"));
+    assert_eq!(leader.gap_to_leader_seconds(), None);
+
+    let lapped = &analysis.gaps.driver("44").unwrap()[0];
+    assert_eq!(lapped.gap_to_leader_seconds(), None);
+    assert_eq!(lapped.interval_seconds(), Some(52.1));
+}
+
+#[test]
+fn finds_drs_trains_at_the_finish_line() {
+    let mut analysis = common::initial(timing_data());
+
+    for racing_number in ["1", "11", "16", "55", "44"] {
+        cross_line(&mut analysis, racing_number, 4);
+    }
+
+    // 55 drops out of range on the next lap
+    update(
+        &mut analysis,
+        "55",
+        json!({ "IntervalToPositionAhead": { "Value": "+1.300" } }),
+        "2024-03-02T15:12:00.000Z",
+    );
+
+    for racing_number in ["1", "11", "16", "55", "44"] {
+        cross_line(&mut analysis, racing_number, 5);
+    }
+
+    assert_eq!(
+        analysis.gaps.drs_trains(),
+        [DrsTrain {
+            lap: 4,
+            racing_numbers: vec!["11".to_owned(), "16".to_owned(), "55".to_owned()],
+        }]
+    );
+}
+
+#[test]
+fn keeps_cars_that_swap_on_the_line() {
+    let mut analysis = common::initial(timing_data());
+
+    cross_line(&mut analysis, "1", 4);
+    cross_line(&mut analysis, "11", 4);
+
+    // 55 gets past 16 on the line, 16 crosses before its own position drops
+    update(
+        &mut analysis,
+        "55",
+        json!({ "Position": "3", "IntervalToPositionAhead": { "Value": "+0.300" }, "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:00.000Z",
+    );
+    update(
+        &mut analysis,
+        "16",
+        json!({ "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:00.100Z",
+    );
+    update(
+        &mut analysis,
+        "16",
+        json!({ "Position": "4", "IntervalToPositionAhead": { "Value": "+0.100" } }),
+        "2024-03-02T15:11:00.150Z",
+    );
+
+    assert_eq!(analysis.gaps.driver("16").unwrap()[0].position, Some(3));
+    assert_eq!(analysis.gaps.driver("55").unwrap()[0].position, Some(3));
+
+    // in the order they crossed, not by their numbers
+    assert_eq!(
+        analysis.gaps.drs_trains(),
+        [DrsTrain {
+            lap: 4,
+            racing_numbers: vec!["11".to_owned(), "55".to_owned(), "16".to_owned()],
+        }]
+    );
+}
+
+#[test]
+fn nothing_to_sample_without_gaps() {
+    // practice and qualifying have no gaps to the leader
+    let mut analysis = common::initial(json!({
+        "TimingData": { "Lines": { "1": { "Position": "1", "NumberOfLaps": 3 } } }
+    }));
+
+    cross_line(&mut analysis, "1", 4);
+
+    assert!(analysis.gaps.is_empty());
+    assert_eq!(analysis.gaps.driver("1"), Some(&[][..]));
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use data::analysis::Analysis;
+use serde_json::{json, Value};
+
+fn timing_data() -> Value {
+    json!({
+        "TimingData": {
+            "Lines": {
+                "1": { "RacingNumber": "1", "NumberOfLaps": 3, "InPit": false, "PitOut": false },
+                "44": { "RacingNumber": "44", "NumberOfLaps": 3, "InPit": false, "PitOut": false }
+            }
+        }
+    })
+}
+
+fn update(analysis: &mut Analysis, line: Value, timestamp: &str) {
+    let update = json!({ "TimingData": { "Lines": { "1": line } } });
+    common::update(analysis, update, Some(timestamp));
+}
+
+// a full lap of driver 1 as the feed sends it, the lap count and time come with the last sector
+fn lap(analysis: &mut Analysis, number: i64, time: &str, sectors: [&str; 3]) {
+    update(
+        analysis,
+        json!({ "Sectors": { "0": { "Value": sectors[0] } }, "Speeds": { "I1": { "Value": "231" } } }),
+        "2024-03-02T15:10:00.000Z",
+    );
+    update(
+        analysis,
+        json!({ "Sectors": { "1": { "Value": sectors[1] } }, "Speeds": { "I2": { "Value": "274" }, "ST": { "Value": "318" } } }),
+        "2024-03-02T15:10:30.000Z",
+    );
+    update(
+        analysis,
+        json!({
+            "Sectors": { "2": { "Value": sectors[2] } },
+            "Speeds": { "FL": { "Value": "289" } },
+            "LastLapTime": { "Value": time },
+            "NumberOfLaps": number
+        }),
+        "2024-03-02T15:11:00.000Z",
+    );
+}
+
+#[test]
+fn records_every_finished_lap() {
+    let mut analysis = common::initial(timing_data());
+
+    lap(&mut analysis, 4, "1:36.101", ["29.511", "39.180", "27.410"]);
+    lap(&mut analysis, 5, "1:35.892", ["29.402", "39.101", "27.389"]);
+
+    let laps = analysis.laps.driver("1").unwrap();
+
+    assert_eq!(laps.len(), 2);
+    assert_eq!(laps[0].number, 4);
+    assert_eq!(laps[1].number, 5);
+    assert_eq!(laps[1].time.as_deref(), Some("1:35.892"));
+    assert_eq!(
+        laps[1].sectors,
+        [
+            Some("29.402".to_owned()),
+            Some("39.101".to_owned()),
+            Some("27.389".to_owned())
+        ]
+    );
+    assert_eq!(laps[1].speeds.st.as_deref(), Some("318"));
+    assert_eq!(laps[1].speeds.fl.as_deref(), Some("289"));
+    assert_eq!(laps[1].utc.as_deref(), Some("2024-03-02T15:11:00.000Z"));
+
+    assert_eq!(analysis.laps.driver("44"), Some(&[][..]));
+}
+
+#[test]
+fn late_values_go_to_the_finished_lap() {
+    let mut analysis = common::initial(timing_data());
+
+    update(
+        &mut analysis,
+        json!({ "Sectors": { "0": { "Value": "29.511" }, "1": { "Value": "39.180" } } }),
+        "2024-03-02T15:10:30.000Z",
+    );
+    update(
+        &mut analysis,
+        json!({ "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:00.000Z",
+    );
+    update(
+        &mut analysis,
+        json!({ "Sectors": { "2": { "Value": "27.410" } }, "LastLapTime": { "Value": "1:36.101" } }),
+        "2024-03-02T15:11:00.200Z",
+    );
+
+    // the sectors are cleared for the new lap
+    update(
+        &mut analysis,
+        json!({ "Sectors": { "0": { "Value": "" }, "1": { "Value": "" }, "2": { "Value": "" } } }),
+        "2024-03-02T15:11:01.000Z",
+    );
+
+    let laps = analysis.laps.driver("1").unwrap();
+
+    assert_eq!(laps.len(), 1);
+    assert_eq!(laps[0].time.as_deref(), Some("1:36.101"));
+    assert_eq!(laps[0].sectors[2].as_deref(), Some("27.410"));
+}
+
+#[test]
+fn flags_pit_laps() {
+    let mut analysis = common::initial(timing_data());
+
+    lap(&mut analysis, 4, "1:36.101", ["29.511", "39.180", "27.410"]);
+
+    update(
+        &mut analysis,
+        json!({ "InPit": true }),
+        "2024-03-02T15:12:20.000Z",
+    );
+    lap(&mut analysis, 5, "1:52.310", ["29.620", "39.310", "43.380"]);
+
+    update(
+        &mut analysis,
+        json!({ "InPit": false, "PitOut": true }),
+        "2024-03-02T15:12:25.000Z",
+    );
+    lap(&mut analysis, 6, "1:55.004", ["47.100", "39.800", "28.104"]);
+
+    let laps = analysis.laps.driver("1").unwrap();
+
+    let flags: Vec<_> = laps
+        .iter()
+        .map(|lap| (lap.number, lap.pit_in, lap.pit_out))
+        .collect();
+    assert_eq!(
+        flags,
+        [(4, false, false), (5, true, false), (6, false, true)]
+    );
+}
+
+#[test]
+fn ignores_resent_lap_counts() {
+    let mut analysis = common::initial(timing_data());
+
+    lap(&mut analysis, 4, "1:36.101", ["29.511", "39.180", "27.410"]);
+
+    // a reconnect sends the initial again, nothing is lost or recorded twice
+    let mut again = timing_data();
+    again["TimingData"]["Lines"]["1"]["NumberOfLaps"] = json!(4);
+    analysis.initial(&common::transformed(again)).unwrap();
+
+    update(
+        &mut analysis,
+        json!({ "NumberOfLaps": 4 }),
+        "2024-03-02T15:11:05.000Z",
+    );
+
+    assert_eq!(analysis.laps.driver("1").unwrap().len(), 1);
+}
+
+#[test]
+fn serializes_by_racing_number() {
+    let mut analysis = common::initial(timing_data());
+
+    lap(&mut analysis, 4, "1:36.101", ["29.511", "39.180", "27.410"]);
+
+    let value = serde_json::to_value(&analysis).unwrap();
+
+    assert_eq!(value["laps"]["44"], json!([]));
+    assert_eq!(
+        value["laps"]["1"][0],
+        json!({
+            "number": 4,
+            "time": "1:36.101",
+            "sectors": ["29.511", "39.180", "27.410"],
+            "speeds": { "i1": "231", "i2": "274", "fl": "289", "st": "318" },
+            "pitIn": false,
+            "pitOut": false,
+            "utc": "2024-03-02T15:11:00.000Z"
+        })
+    );
+}
@@ -0,0 +1,322 @@
+use data::{
+    merge::{merge, merge_with, Strategies, Strategy},
+    transformer,
+};
+use serde_json::{json, Value};
+
+// the state as live keeps it, transformed before it is merged
+fn state(initial: Value) -> Value {
+    let mut state = initial;
+    transformer::transform(&mut state);
+    state
+}
+
+fn apply(state: &mut Value, update: Value) {
+    let mut update = update;
+    transformer::transform(&mut update);
+    merge(state, update);
+}
+
+fn timing_app_data() -> Value {
+    json!({
+        "TimingAppData": {
+            "Lines": {
+                "1": {
+                    "RacingNumber": "1",
+                    "Line": 1,
+                    "Stints": [
+                        { "LapFlags": 0, "Compound": "SOFT", "New": "true", "TyresNotChanged": "0", "TotalLaps": 12, "StartLaps": 0 },
+                        { "LapFlags": 0, "Compound": "HARD", "New": "true", "TyresNotChanged": "0", "TotalLaps": 31, "StartLaps": 0 },
+                        { "LapFlags": 0, "Compound": "HARD", "New": "true", "TyresNotChanged": "0", "TotalLaps": 1, "StartLaps": 0 }
+                    ]
+                },
+                "44": {
+                    "RacingNumber": "44",
+                    "Line": 2,
+                    "Stints": [
+                        { "LapFlags": 0, "Compound": "MEDIUM", "New": "true", "TyresNotChanged": "0", "TotalLaps": 20, "StartLaps": 0 }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+#[test]
+fn removes_deleted_stints() {
+    let mut state = state(timing_app_data());
+
+    // the stint of a pit stop that was reverted
+    apply(
+        &mut state,
+        json!({ "TimingAppData": { "Lines": { "1": { "Stints": { "_deleted": [2] } } } } }),
+    );
+
+    let stints = state["timingAppData"]["lines"]["1"]["stints"]
+        .as_array()
+        .unwrap();
+
+    assert_eq!(stints.len(), 2);
+    assert_eq!(stints[1]["compound"], "HARD");
+    assert_eq!(stints[1]["totalLaps"], 31);
+}
+
+#[test]
+fn deletes_before_patching() {
+    let mut state = state(timing_app_data());
+
+    // a corrected stint, the wrong one is retracted and the right one sent in the same update
+    apply(
+        &mut state,
+        json!({
+            "TimingAppData": {
+                "Lines": {
+                    "1": {
+                        "Stints": {
+                            "_deleted": ["1", "2"],
+                            "1": { "LapFlags": 0, "Compound": "MEDIUM", "New": "false", "TyresNotChanged": "0", "TotalLaps": 8, "StartLaps": 8 }
+                        }
+                    }
+                }
+            }
+        }),
+    );
+
+    let stints = state["timingAppData"]["lines"]["1"]["stints"]
+        .as_array()
+        .unwrap();
+
+    assert_eq!(stints.len(), 2);
+    assert_eq!(stints[0]["compound"], "SOFT");
+    assert_eq!(stints[1]["compound"], "MEDIUM");
+    assert_eq!(stints[1]["startLaps"], 8);
+}
+
+#[test]
+fn removes_deleted_keys() {
+    let mut state = state(json!({
+        "TimingData": {
+            "Lines": {
+                "1": { "RacingNumber": "1", "Line": 1, "GapToLeader": "" },
+                "44": { "RacingNumber": "44", "Line": 2, "GapToLeader": "+1.234" },
+                "81": { "RacingNumber": "81", "Line": 3, "GapToLeader": "+2.101" }
+            }
+        }
+    }));
+
+    apply(
+        &mut state,
+        json!({
+            "TimingData": {
+                "Lines": {
+                    "_deleted": ["81"],
+                    "44": { "GapToLeader": "+1.002" }
+                }
+            }
+        }),
+    );
+
+    let lines = state["timingData"]["lines"].as_object().unwrap();
+
+    assert_eq!(lines.len(), 2);
+    assert!(!lines.contains_key("81"));
+    assert_eq!(lines["44"]["gapToLeader"], "+1.002");
+}
+
+#[test]
+fn renames_deleted_keys_like_the_state() {
+    let mut state = state(json!({
+        "TimingStats": {
+            "Lines": {
+                "1": {
+                    "PersonalBestLapTime": { "Value": "1:32.608", "Lap": 39, "Position": 1 },
+                    "BestSpeeds": { "ST": { "Value": "318", "Position": 4 } }
+                }
+            }
+        }
+    }));
+
+    apply(
+        &mut state,
+        json!({ "TimingStats": { "Lines": { "1": { "_deleted": ["PersonalBestLapTime"] } } } }),
+    );
+
+    let line = state["timingStats"]["lines"]["1"].as_object().unwrap();
+
+    assert!(!line.contains_key("personalBestLapTime"));
+    assert!(line.contains_key("bestSpeeds"));
+}
+
+#[test]
+fn never_keeps_the_marker() {
+    let mut state = state(json!({ "TimingAppData": { "Lines": {} } }));
+
+    // nothing to delete for a driver we don't know yet
+    apply(
+        &mut state,
+        json!({
+            "TimingAppData": {
+                "Lines": {
+                    "63": {
+                        "Stints": { "_deleted": [0], "0": { "Compound": "INTERMEDIATE", "New": "true" } }
+                    }
+                }
+            }
+        }),
+    );
+
+    apply(
+        &mut state,
+        json!({ "TimingAppData": { "Lines": { "4": { "Stints": [{ "Compound": "WET", "_deleted": [] }] } } } }),
+    );
+
+    assert!(!state.to_string().contains("_deleted"));
+
+    assert_eq!(
+        state["timingAppData"]["lines"]["63"]["stints"],
+        json!({ "0": { "compound": "INTERMEDIATE", "new": "true" } })
+    );
+}
+
+#[test]
+fn keeps_merging_without_deletions() {
+    let mut state = state(timing_app_data());
+
+    apply(
+        &mut state,
+        json!({ "TimingAppData": { "Lines": { "44": { "Stints": { "0": { "TotalLaps": 21 } } } } } }),
+    );
+
+    apply(
+        &mut state,
+        json!({ "TimingAppData": { "Lines": { "44": { "Stints": [{ "Compound": "HARD", "New": "true", "TotalLaps": 0 }] } } } }),
+    );
+
+    let stints = state["timingAppData"]["lines"]["44"]["stints"]
+        .as_array()
+        .unwrap();
+
+    assert_eq!(stints.len(), 2);
+    assert_eq!(stints[0]["totalLaps"], 21);
+    assert_eq!(stints[1]["compound"], "HARD");
+}
+
+fn apply_feed(state: &mut Value, update: Value) {
+    let mut update = update;
+    transformer::transform(&mut update);
+    merge_with(state, update, &Strategies::feed());
+}
+
+#[test]
+fn replaces_car_data() {
+    let mut state = state(json!({
+        "CarData": { "Entries": [{ "Utc": "2024-03-02T15:04:05.123Z", "Cars": {} }] }
+    }));
+
+    apply_feed(
+        &mut state,
+        json!({ "CarData": { "Entries": [{ "Utc": "2024-03-02T15:04:05.423Z", "Cars": {} }] } }),
+    );
+
+    assert_eq!(
+        state["carData"]["entries"],
+        json!([{ "utc": "2024-03-02T15:04:05.423Z", "cars": {} }])
+    );
+}
+
+#[test]
+fn appends_race_control_messages() {
+    let mut state = state(json!({
+        "RaceControlMessages": { "Messages": [{ "Utc": "2024-03-02T15:03:00", "Category": "Flag", "Flag": "GREEN" }] }
+    }));
+
+    apply_feed(
+        &mut state,
+        json!({ "RaceControlMessages": { "Messages": [{ "Utc": "2024-03-02T15:10:12", "Category": "Drs", "Status": "ENABLED" }] } }),
+    );
+
+    let messages = state["raceControlMessages"]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1]["category"], "Drs");
+}
+
+#[test]
+fn patches_resent_stints_by_index() {
+    let mut state = state(timing_app_data());
+
+    // the whole list again after a reconnect, appending would double every stint
+    apply_feed(
+        &mut state,
+        json!({
+            "TimingAppData": {
+                "Lines": {
+                    "44": {
+                        "Stints": [
+                            { "LapFlags": 0, "Compound": "MEDIUM", "New": "true", "TyresNotChanged": "0", "TotalLaps": 22, "StartLaps": 0 }
+                        ]
+                    }
+                }
+            }
+        }),
+    );
+
+    let stints = state["timingAppData"]["lines"]["44"]["stints"]
+        .as_array()
+        .unwrap();
+
+    assert_eq!(stints.len(), 1);
+    assert_eq!(stints[0]["totalLaps"], 22);
+}
+
+#[test]
+fn merges_team_radio_by_path() {
+    let mut state = state(json!({
+        "TeamRadio": { "Captures": [{ "Utc": "2024-03-02T15:20:01.1Z", "RacingNumber": "1", "Path": "TeamRadio/MAXVER01_1_20240302_152001.mp3" }] }
+    }));
+
+    apply_feed(
+        &mut state,
+        json!({
+            "TeamRadio": {
+                "Captures": [
+                    { "Utc": "2024-03-02T15:20:01.1Z", "RacingNumber": "1", "Path": "TeamRadio/MAXVER01_1_20240302_152001.mp3" },
+                    { "Utc": "2024-03-02T15:24:40.9Z", "RacingNumber": "16", "Path": "TeamRadio/CHALEC01_16_20240302_152440.mp3" }
+                ]
+            }
+        }),
+    );
+
+    let captures = state["teamRadio"]["captures"].as_array().unwrap();
+    assert_eq!(captures.len(), 2);
+    assert_eq!(captures[1]["racingNumber"], "16");
+}
+
+#[test]
+fn matches_paths_in_any_casing() {
+    let strategies = Strategies::new().rule("timingAppData/lines/*/stints", Strategy::Replace);
+
+    let mut raw =
+        json!({ "TimingAppData": { "Lines": { "1": { "Stints": [{ "Compound": "SOFT" }] } } } });
+    merge_with(
+        &mut raw,
+        json!({ "TimingAppData": { "Lines": { "1": { "Stints": [{ "Compound": "HARD" }] } } } }),
+        &strategies,
+    );
+
+    let mut snake =
+        json!({ "timing_app_data": { "lines": { "1": { "stints": [{ "compound": "SOFT" }] } } } });
+    merge_with(
+        &mut snake,
+        json!({ "timing_app_data": { "lines": { "1": { "stints": [{ "compound": "HARD" }] } } } }),
+        &strategies,
+    );
+
+    assert_eq!(
+        raw["TimingAppData"]["Lines"]["1"]["Stints"],
+        json!([{ "Compound": "HARD" }])
+    );
+    assert_eq!(
+        snake["timing_app_data"]["lines"]["1"]["stints"],
+        json!([{ "compound": "HARD" }])
+    );
+}
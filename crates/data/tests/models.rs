@@ -0,0 +1,144 @@
+mod common;
+
+use common::transformed;
+use data::{
+    merge::{merge_with, Strategies},
+    models::{Indexed, Topic, Update},
+};
+use serde_json::{json, Value};
+
+// a line of the initial as f1 sends it, the sectors in full
+fn timing_data() -> Value {
+    json!({
+        "TimingData": {
+            "Lines": {
+                "44": {
+                    "RacingNumber": "44",
+                    "Position": "5",
+                    "NumberOfLaps": 11,
+                    "Sectors": [
+                        { "Stopped": false, "Value": "29.511", "Segments": [{ "Status": 2049 }, { "Status": 2049 }] },
+                        { "Stopped": false, "Value": "39.180", "Segments": [{ "Status": 2049 }] },
+                        { "Stopped": false, "Value": "27.410", "Segments": [{ "Status": 2051 }] }
+                    ],
+                    "LastLapTime": { "Value": "1:36.101", "PersonalFastest": false }
+                }
+            }
+        }
+    })
+}
+
+fn parse(key: &str, data: &Value) -> Update {
+    Update::parse(key, data).unwrap()
+}
+
+#[test]
+fn full_lists_and_index_objects() {
+    let full: Indexed<i64> = serde_json::from_value(json!([4, 8, 15])).unwrap();
+
+    assert!(full.is_complete());
+    assert_eq!(full.len(), 3);
+    assert_eq!(full.last(), Some(&15));
+    assert_eq!(serde_json::to_value(&full).unwrap(), json!([4, 8, 15]));
+
+    // an update only sends the items that changed
+    let partial: Indexed<i64> = serde_json::from_value(json!({ "3": 16, "1": 23 })).unwrap();
+
+    assert!(!partial.is_complete());
+    assert_eq!(partial.get(1), Some(&23));
+    assert_eq!(partial.get(0), None);
+    assert_eq!(
+        serde_json::to_value(&partial).unwrap(),
+        json!({ "1": 23, "3": 16 })
+    );
+
+    // the index keys sorted as numbers, not as strings
+    let late: Indexed<i64> = serde_json::from_value(json!({ "10": 1, "9": 2 })).unwrap();
+    assert_eq!(late.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [9, 10]);
+}
+
+#[test]
+fn leaves_deleted_markers_out() {
+    let sectors: Indexed<i64> =
+        serde_json::from_value(json!({ "_deleted": ["2"], "0": 7 })).unwrap();
+    assert_eq!(sectors, Indexed([(0, 7)].into_iter().collect()));
+
+    let update = transformed(json!({
+        "TimingData": { "Lines": { "44": { "_deleted": ["Stats"], "Sectors": { "_deleted": [1], "0": { "Value": "" } } } } }
+    }));
+
+    let Update::TimingData(timing_data) = parse("timingData", &update["timingData"]) else {
+        panic!("timingData should parse as timing data");
+    };
+
+    let sectors = timing_data.lines.unwrap()["44"].sectors.clone().unwrap();
+    assert_eq!(sectors.len(), 1);
+    assert_eq!(sectors.get(0).unwrap().value.as_deref(), Some(""));
+}
+
+#[test]
+fn parses_by_topic_key() {
+    let update = transformed(json!({ "LapCount": { "CurrentLap": 12 } }));
+    let lap_count = parse("lapCount", &update["lapCount"]);
+
+    assert_eq!(lap_count.topic(), Some(Topic::LapCount));
+    assert!(matches!(lap_count, Update::LapCount(lap) if lap.current_lap == Some(12)));
+
+    // what we have no model for is kept as it is
+    let unknown = parse("driverRaceInfo", &json!({ "1": { "position": "1" } }));
+    assert_eq!(unknown.topic(), None);
+    assert_eq!(
+        unknown,
+        Update::Unknown(
+            "driverRaceInfo".to_owned(),
+            json!({ "1": { "position": "1" } })
+        )
+    );
+
+    // a model that doesn't fit is an error, not an unknown topic
+    assert!(Update::parse("lapCount", &json!({ "currentLap": "twelve" })).is_err());
+}
+
+#[test]
+fn partial_line_applied_to_the_state() {
+    let mut state = transformed(timing_data());
+
+    // the first sector of the next lap, the rest of the line is left alone
+    let update = transformed(json!({
+        "TimingData": { "Lines": { "44": { "Sectors": { "0": { "Value": "29.402", "Segments": { "1": { "Status": 2051 } } } } } } }
+    }));
+
+    let Update::TimingData(partial) = parse("timingData", &update["timingData"]) else {
+        panic!("timingData should parse as timing data");
+    };
+    let partial_sectors = partial.lines.unwrap()["44"].sectors.clone().unwrap();
+    assert_eq!(partial_sectors.len(), 1);
+    assert_eq!(
+        partial_sectors.get(0).unwrap().value.as_deref(),
+        Some("29.402")
+    );
+
+    merge_with(&mut state, update, &Strategies::feed());
+
+    let Update::TimingData(timing_data) = parse("timingData", &state["timingData"]) else {
+        panic!("timingData should parse as timing data");
+    };
+    let line = &timing_data.lines.unwrap()["44"];
+
+    let sectors = line.sectors.as_ref().unwrap();
+    assert!(sectors.is_complete());
+    assert_eq!(sectors.len(), 3);
+    assert_eq!(sectors.get(0).unwrap().value.as_deref(), Some("29.402"));
+    assert_eq!(sectors.get(1).unwrap().value.as_deref(), Some("39.180"));
+
+    let segments = sectors.get(0).unwrap().segments.as_ref().unwrap();
+    assert_eq!(segments.get(0).unwrap().status, Some(2049));
+    assert_eq!(segments.get(1).unwrap().status, Some(2051));
+
+    assert_eq!(line.position.as_deref(), Some("5"));
+    assert_eq!(line.number_of_laps, Some(11));
+    assert_eq!(
+        line.last_lap_time.as_ref().unwrap().value.as_deref(),
+        Some("1:36.101")
+    );
+}
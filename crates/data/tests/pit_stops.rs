@@ -0,0 +1,156 @@
+mod common;
+
+use data::analysis::Analysis;
+use serde_json::{json, Value};
+
+fn session() -> Value {
+    json!({
+        "TimingData": {
+            "Lines": {
+                "1": { "RacingNumber": "1", "NumberOfLaps": 17, "InPit": false, "PitOut": false },
+                "44": { "RacingNumber": "44", "NumberOfLaps": 17, "InPit": false, "PitOut": false }
+            }
+        },
+        "TimingAppData": {
+            "Lines": {
+                "1": { "RacingNumber": "1", "Stints": [{ "Compound": "SOFT", "New": "true", "TotalLaps": 17, "StartLaps": 0 }] },
+                "44": { "RacingNumber": "44", "Stints": [{ "Compound": "MEDIUM", "New": "true", "TotalLaps": 17, "StartLaps": 0 }] }
+            }
+        },
+        "PitLaneTimeCollection": { "PitTimes": {} }
+    })
+}
+
+fn update(analysis: &mut Analysis, update: Value, timestamp: &str) {
+    common::update(analysis, update, Some(timestamp));
+}
+
+fn timing_data(line: Value) -> Value {
+    json!({ "TimingData": { "Lines": { "1": line } } })
+}
+
+// driver 1 pits at the end of lap 18 for new hards
+fn pit_stop(analysis: &mut Analysis) {
+    update(
+        analysis,
+        timing_data(json!({ "InPit": true })),
+        "2024-03-02T15:31:02.100Z",
+    );
+    update(
+        analysis,
+        timing_data(json!({ "NumberOfLaps": 18 })),
+        "2024-03-02T15:31:10.400Z",
+    );
+    update(
+        analysis,
+        json!({ "TimingAppData": { "Lines": { "1": { "Stints": { "1": { "LapFlags": 0, "TotalLaps": 0, "StartLaps": 0 } } } } } }),
+        "2024-03-02T15:31:14.000Z",
+    );
+    update(
+        analysis,
+        json!({ "TimingAppData": { "Lines": { "1": { "Stints": { "1": { "Compound": "HARD", "New": "true" } } } } } }),
+        "2024-03-02T15:31:14.200Z",
+    );
+    update(
+        analysis,
+        timing_data(json!({ "InPit": false, "PitOut": true })),
+        "2024-03-02T15:31:24.900Z",
+    );
+    update(
+        analysis,
+        json!({ "PitLaneTimeCollection": { "PitTimes": { "1": { "RacingNumber": "1", "Duration": "22.8", "Lap": "18" } } } }),
+        "2024-03-02T15:31:25.300Z",
+    );
+}
+
+#[test]
+fn records_a_stop() {
+    let mut analysis = common::initial(session());
+
+    pit_stop(&mut analysis);
+
+    assert_eq!(
+        serde_json::to_value(&analysis.pit_stops).unwrap(),
+        json!({
+            "1": [{
+                "number": 1,
+                "lap": 18,
+                "entry": "2024-03-02T15:31:02.100Z",
+                "exit": "2024-03-02T15:31:24.900Z",
+                "laneTime": "22.8",
+                "tyreChange": true,
+                "compound": "HARD",
+                "newTyres": true
+            }],
+            "44": []
+        })
+    );
+}
+
+#[test]
+fn keeps_every_stop_after_the_lane_time_is_deleted() {
+    let mut analysis = common::initial(session());
+
+    pit_stop(&mut analysis);
+
+    // the feed only keeps the latest lane time and deletes it after a while
+    update(
+        &mut analysis,
+        json!({ "PitLaneTimeCollection": { "PitTimes": { "_deleted": ["1"] } } }),
+        "2024-03-02T15:32:25.000Z",
+    );
+
+    // a drive through, no tyres
+    update(
+        &mut analysis,
+        timing_data(json!({ "NumberOfLaps": 30 })),
+        "2024-03-02T15:50:00.000Z",
+    );
+    update(
+        &mut analysis,
+        timing_data(json!({ "InPit": true })),
+        "2024-03-02T15:51:31.000Z",
+    );
+    update(
+        &mut analysis,
+        timing_data(json!({ "InPit": false, "PitOut": true })),
+        "2024-03-02T15:51:47.000Z",
+    );
+    update(
+        &mut analysis,
+        json!({ "PitLaneTimeCollection": { "PitTimes": { "1": { "RacingNumber": "1", "Duration": "16.2", "Lap": "31" } } } }),
+        "2024-03-02T15:51:48.000Z",
+    );
+
+    let stops = analysis.pit_stops.driver("1").unwrap();
+
+    assert_eq!(stops.len(), 2);
+    assert_eq!(stops[0].lane_time.as_deref(), Some("22.8"));
+    assert_eq!(stops[1].number, 2);
+    assert_eq!(stops[1].lap, Some(31));
+    assert_eq!(stops[1].lane_time.as_deref(), Some("16.2"));
+    assert!(!stops[1].tyre_change);
+}
+
+#[test]
+fn records_stops_only_known_by_lane_time() {
+    let mut analysis = common::initial(session());
+
+    update(
+        &mut analysis,
+        json!({ "PitLaneTimeCollection": { "PitTimes": { "44": { "RacingNumber": "44", "Duration": "23.4", "Lap": "18" } } } }),
+        "2024-03-02T15:31:25.300Z",
+    );
+
+    // resent after a reconnect
+    let mut again = session();
+    again["PitLaneTimeCollection"] =
+        json!({ "PitTimes": { "44": { "RacingNumber": "44", "Duration": "23.4", "Lap": "18" } } });
+    analysis.initial(&common::transformed(again)).unwrap();
+
+    let stops = analysis.pit_stops.driver("44").unwrap();
+
+    assert_eq!(stops.len(), 1);
+    assert_eq!(stops[0].lap, Some(18));
+    assert_eq!(stops[0].entry, None);
+}
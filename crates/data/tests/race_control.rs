@@ -0,0 +1,231 @@
+mod common;
+
+use common::transformed;
+use data::{
+    analysis::race_control::{
+        InvestigationStatus, Penalty, RaceControl, RaceControlEvent, SafetyCarStatus,
+    },
+    models::{RaceControlMessage, RaceControlMessages},
+};
+use serde_json::{json, Value};
+
+fn message(raw: Value) -> RaceControlMessage {
+    serde_json::from_value(transformed(raw)).unwrap()
+}
+
+fn classify(raw: Value) -> RaceControlEvent {
+    RaceControlEvent::classify(&message(raw))
+}
+
+fn other(text: &str) -> Value {
+    json!({ "Utc": "2024-03-02T15:40:12", "Lap": 22, "Category": "Other", "Message": text })
+}
+
+#[test]
+fn flags_by_sector_and_driver() {
+    assert_eq!(
+        classify(
+            json!({ "Category": "Flag", "Flag": "DOUBLE YELLOW", "Scope": "Sector", "Sector": 7, "Message": "DOUBLE YELLOW IN TRACK SECTOR 7" })
+        ),
+        RaceControlEvent::Flag {
+            flag: "DOUBLE YELLOW".to_owned(),
+            scope: Some("Sector".to_owned()),
+            sector: Some(7),
+            racing_number: None,
+        }
+    );
+
+    assert_eq!(
+        classify(
+            json!({ "Category": "Flag", "Flag": "BLUE", "Scope": "Driver", "RacingNumber": "2", "Message": "WAVED BLUE FLAG FOR CAR 2 (SAR) TIMED AT 15:40:01" })
+        ),
+        RaceControlEvent::Flag {
+            flag: "BLUE".to_owned(),
+            scope: Some("Driver".to_owned()),
+            sector: None,
+            racing_number: Some("2".to_owned()),
+        }
+    );
+}
+
+#[test]
+fn penalties() {
+    assert_eq!(
+        classify(other(
+            "FIA STEWARDS: 5 SECOND TIME PENALTY FOR CAR 1 (VER) - CAUSING A COLLISION"
+        )),
+        RaceControlEvent::Penalty {
+            racing_number: Some("1".to_owned()),
+            penalty: Penalty::Time,
+            seconds: Some(5),
+            places: None,
+            reason: Some("CAUSING A COLLISION".to_owned()),
+        }
+    );
+
+    assert_eq!(
+        classify(other(
+            "FIA STEWARDS: DRIVE THROUGH PENALTY FOR CAR 20 (MAG) - SPEEDING IN THE PIT LANE"
+        )),
+        RaceControlEvent::Penalty {
+            racing_number: Some("20".to_owned()),
+            penalty: Penalty::DriveThrough,
+            seconds: None,
+            places: None,
+            reason: Some("SPEEDING IN THE PIT LANE".to_owned()),
+        }
+    );
+
+    assert!(matches!(
+        classify(other(
+            "FIA STEWARDS: 10 SECOND STOP/GO PENALTY FOR CAR 24 (ZHO) - UNSAFE RELEASE"
+        )),
+        RaceControlEvent::Penalty {
+            penalty: Penalty::StopGo,
+            seconds: Some(10),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn served_penalties() {
+    assert_eq!(
+        classify(other(
+            "PENALTY SERVED: 5 SECOND TIME PENALTY FOR CAR 1 (VER) - CAUSING A COLLISION"
+        )),
+        RaceControlEvent::PenaltyServed {
+            racing_number: Some("1".to_owned()),
+            penalty: Some(Penalty::Time),
+            seconds: Some(5),
+        }
+    );
+
+    // not a new penalty for car 20
+    assert_eq!(
+        classify(other("CAR 20 (MAG) PENALTY SERVED")),
+        RaceControlEvent::PenaltyServed {
+            racing_number: Some("20".to_owned()),
+            penalty: None,
+            seconds: None,
+        }
+    );
+}
+
+#[test]
+fn investigations() {
+    assert_eq!(
+        classify(other("FIA STEWARDS: TURN 1 INCIDENT INVOLVING CARS 1 (VER) AND 44 (HAM) UNDER INVESTIGATION - CAUSING A COLLISION")),
+        RaceControlEvent::Investigation {
+            status: InvestigationStatus::Opened,
+            racing_numbers: vec!["1".to_owned(), "44".to_owned()],
+            reason: Some("CAUSING A COLLISION".to_owned()),
+        }
+    );
+
+    assert!(matches!(
+        classify(other(
+            "FIA STEWARDS: TURN 4 INCIDENT INVOLVING CAR 18 (STR) NOTED - LEAVING THE TRACK"
+        )),
+        RaceControlEvent::Investigation {
+            status: InvestigationStatus::Noted,
+            ..
+        }
+    ));
+
+    // a penalty was looked at but not given
+    assert!(matches!(
+        classify(other("FIA STEWARDS: TURN 1 INCIDENT INVOLVING CARS 1 (VER) AND 44 (HAM) REVIEWED NO FURTHER INVESTIGATION")),
+        RaceControlEvent::Investigation { status: InvestigationStatus::Closed, .. }
+    ));
+}
+
+#[test]
+fn deleted_laps() {
+    assert_eq!(
+        classify(other(
+            "CAR 14 (ALO) TIME 1:31.522 DELETED - TRACK LIMITS AT TURN 4 LAP 12 15:12:33"
+        )),
+        RaceControlEvent::LapDeleted {
+            racing_number: Some("14".to_owned()),
+            time: Some("1:31.522".to_owned()),
+            lap: Some(12),
+            reason: Some("TRACK LIMITS AT TURN 4".to_owned()),
+        }
+    );
+
+    // without a lap in the text it is the lap the message was sent on
+    assert_eq!(
+        classify(other("CAR 44 (HAM) LAP DELETED - TRACK LIMITS AT TURN 10")),
+        RaceControlEvent::LapDeleted {
+            racing_number: Some("44".to_owned()),
+            time: None,
+            lap: Some(22),
+            reason: Some("TRACK LIMITS AT TURN 10".to_owned()),
+        }
+    );
+}
+
+#[test]
+fn drs_and_safety_car() {
+    assert_eq!(
+        classify(json!({ "Category": "Drs", "Status": "ENABLED", "Message": "DRS ENABLED" })),
+        RaceControlEvent::Drs { enabled: true }
+    );
+    assert_eq!(
+        classify(json!({ "Category": "Drs", "Status": "DISABLED", "Message": "DRS DISABLED" })),
+        RaceControlEvent::Drs { enabled: false }
+    );
+
+    assert_eq!(
+        classify(
+            json!({ "Category": "SafetyCar", "Status": "DEPLOYED", "Mode": "SAFETY CAR", "Message": "SAFETY CAR DEPLOYED" })
+        ),
+        RaceControlEvent::SafetyCar {
+            r#virtual: false,
+            status: SafetyCarStatus::Deployed
+        }
+    );
+    assert_eq!(
+        classify(
+            json!({ "Category": "SafetyCar", "Status": "ENDING", "Mode": "VIRTUAL SAFETY CAR", "Message": "VIRTUAL SAFETY CAR ENDING" })
+        ),
+        RaceControlEvent::SafetyCar {
+            r#virtual: true,
+            status: SafetyCarStatus::Ending
+        }
+    );
+}
+
+#[test]
+fn keeps_the_raw_messages_next_to_the_events() {
+    let messages: RaceControlMessages = serde_json::from_value(transformed(json!({
+            "Messages": [
+                { "Utc": "2024-03-02T15:00:00", "Category": "Flag", "Flag": "GREEN", "Scope": "Track", "Message": "GREEN LIGHT - PIT EXIT OPEN" },
+                other("RISK OF RAIN FOR F1 RACE IS 0%"),
+                { "Utc": "2024-03-02T15:03:00", "Category": "Drs", "Status": "ENABLED", "Message": "DRS ENABLED" }
+            ]
+    })))
+    .unwrap();
+
+    let race_control = serde_json::to_value(RaceControl::new(&messages)).unwrap();
+
+    assert_eq!(race_control["messages"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        race_control["messages"][1]["message"],
+        "RISK OF RAIN FOR F1 RACE IS 0%"
+    );
+    assert_eq!(
+        race_control["events"],
+        json!([
+            { "type": "flag", "flag": "GREEN", "scope": "Track", "sector": null, "racingNumber": null },
+            { "type": "other" },
+            { "type": "drs", "enabled": true }
+        ])
+    );
+
+    assert_eq!(
+        serde_json::to_value(classify(json!({ "Category": "SafetyCar", "Status": "IN THIS LAP", "Mode": "SAFETY CAR", "Message": "SAFETY CAR IN THIS LAP" }))).unwrap(),
+        json!({ "type": "safetyCar", "virtual": false, "status": "inThisLap" })
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::transformed;
+use data::{
+    analysis::stints::Stints,
+    models::{SessionInfo, TimingAppData},
+};
+use serde_json::{json, Value};
+
+fn session(kind: &str, name: &str) -> SessionInfo {
+    serde_json::from_value(transformed(json!({ "Type": kind, "Name": name }))).unwrap()
+}
+
+fn timing_app_data(lines: Value) -> TimingAppData {
+    serde_json::from_value(transformed(json!({ "Lines": lines }))).unwrap()
+}
+
+fn stint(compound: &str, new: &str, start_laps: i64, total_laps: i64) -> Value {
+    json!({ "LapFlags": 0, "Compound": compound, "New": new, "TyresNotChanged": "0", "StartLaps": start_laps, "TotalLaps": total_laps })
+}
+
+#[test]
+fn counts_laps_per_stint() {
+    let data = timing_app_data(json!({
+        "1": { "RacingNumber": "1", "Stints": [stint("SOFT", "true", 0, 17), stint("HARD", "false", 3, 28), stint("SOFT", "true", 0, 0)] }
+    }));
+
+    let stints = Stints::new(&data, Some(&session("Race", "Race")));
+    let driver = &stints.drivers["1"];
+
+    let laps: Vec<_> = driver
+        .stints
+        .iter()
+        .map(|stint| (stint.start_lap, stint.end_lap, stint.laps, stint.tyre_laps))
+        .collect();
+    assert_eq!(
+        laps,
+        [
+            (1, Some(17), 17, 17),
+            (18, Some(42), 25, 28),
+            (43, None, 0, 0)
+        ]
+    );
+
+    assert_eq!(driver.stints[1].new, Some(false));
+    assert_eq!(driver.stints[1].number, 2);
+    assert!(driver.stints[2].current);
+    assert!(!driver.stints[1].current);
+}
+
+#[test]
+fn flags_missing_second_compound_in_dry_races() {
+    let data = timing_app_data(json!({
+        "1": { "Stints": [stint("MEDIUM", "true", 0, 20), stint("HARD", "true", 0, 5)] },
+        "44": { "Stints": [stint("HARD", "true", 0, 20), stint("HARD", "true", 0, 5)] }
+    }));
+
+    let stints = Stints::new(&data, Some(&session("Race", "Race")));
+
+    assert!(stints.race);
+    assert!(!stints.drivers["1"].needs_second_compound);
+    assert!(stints.drivers["44"].needs_second_compound);
+    assert_eq!(stints.drivers["44"].dry_compounds, ["HARD"]);
+}
+
+#[test]
+fn no_compound_rule_when_not_a_race() {
+    let dry = timing_app_data(json!({ "44": { "Stints": [stint("HARD", "true", 0, 20)] } }));
+
+    for session in [
+        session("Race", "Sprint"),
+        session("Qualifying", "Qualifying"),
+    ] {
+        let stints = Stints::new(&dry, Some(&session));
+        assert!(!stints.race);
+        assert!(!stints.drivers["44"].needs_second_compound);
+    }
+}
+
+#[test]
+fn wet_tyres_only_exempt_the_driver_on_them() {
+    let data = timing_app_data(json!({
+        "1": { "Stints": [stint("INTERMEDIATE", "true", 0, 20), stint("HARD", "true", 0, 5)] },
+        "44": { "Stints": [stint("HARD", "true", 0, 20)] }
+    }));
+
+    let stints = Stints::new(&data, Some(&session("Race", "Race")));
+
+    assert!(stints.race);
+    assert!(!stints.drivers["1"].needs_second_compound);
+    assert!(stints.drivers["44"].needs_second_compound);
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use data::models::{Car, CarData, Channel, Drs, Telemetry};
+use serde_json::{json, Value};
+
+fn car_data(entries: Value) -> Value {
+    json!({ "CarData": { "Entries": entries } })
+}
+
+fn entry(utc: &str, speed: i64, drs: i64) -> Value {
+    json!({
+        "Utc": utc,
+        "Cars": {
+            "1": { "Channels": { "0": 11210, "2": speed, "3": 7, "4": 100, "5": 0, "45": drs } },
+            "44": { "Channels": { "0": 10544, "2": 131, "3": 3, "4": 0, "5": 100, "45": 1 } }
+        }
+    })
+}
+
+#[test]
+fn names_the_channels() {
+    let data = car_data(json!([entry("2024-03-02T15:04:05.123Z", 298, 12)]));
+    let data: CarData =
+        serde_json::from_value(common::transformed(data)["carData"].clone()).unwrap();
+
+    let samples: Vec<(&String, Telemetry)> = data
+        .entries
+        .as_ref()
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .telemetry()
+        .collect();
+
+    assert_eq!(
+        samples[0],
+        (
+            &"1".to_owned(),
+            Telemetry {
+                utc: Some("2024-03-02T15:04:05.123Z".to_owned()),
+                rpm: Some(11210),
+                speed: Some(298),
+                gear: Some(7),
+                throttle: Some(100),
+                brake: Some(false),
+                drs: Some(Drs::Open),
+            }
+        )
+    );
+    assert_eq!(samples[1].1.brake, Some(true));
+    assert_eq!(samples[1].1.drs, Some(Drs::Off));
+
+    assert_eq!(Channel::from_id("45"), Some(Channel::Drs));
+    assert_eq!(Channel::from_id("1"), None);
+    assert_eq!(Drs::from_channel(8), Drs::Eligible);
+
+    // unknown channels are left alone and missing ones stay empty
+    let car: Car = serde_json::from_value(json!({ "channels": { "2": 80, "99": 1 } })).unwrap();
+    let telemetry = car.telemetry(None);
+    assert_eq!(telemetry.speed, Some(80));
+    assert_eq!(telemetry.rpm, None);
+}
+
+#[test]
+fn keeps_the_latest_sample_per_driver() {
+    let mut analysis = common::initial(car_data(json!([entry(
+        "2024-03-02T15:04:05.123Z",
+        280,
+        12
+    )])));
+
+    common::update(
+        &mut analysis,
+        car_data(json!([
+            entry("2024-03-02T15:04:05.403Z", 291, 12),
+            entry("2024-03-02T15:04:05.683Z", 298, 14)
+        ])),
+        Some("2024-03-02T15:04:06.010Z"),
+    );
+
+    // a batch that arrives late does not replace newer samples
+    common::update(
+        &mut analysis,
+        car_data(json!([entry("2024-03-02T15:04:05.203Z", 284, 12)])),
+        Some("2024-03-02T15:04:06.210Z"),
+    );
+
+    let latest = analysis.telemetry.driver("1").unwrap();
+    assert_eq!(latest.utc.as_deref(), Some("2024-03-02T15:04:05.683Z"));
+    assert_eq!(latest.speed, Some(298));
+
+    assert_eq!(
+        serde_json::to_value(&analysis.telemetry).unwrap()["44"],
+        json!({
+            "utc": "2024-03-02T15:04:05.683Z",
+            "rpm": 10544,
+            "speed": 131,
+            "gear": 3,
+            "throttle": 0,
+            "brake": true,
+            "drs": "off"
+        })
+    );
+}
+
+#[test]
+fn compares_samples_by_time_not_text() {
+    let mut analysis = common::initial(car_data(json!([entry(
+        "2024-03-02T15:04:05.6834567Z",
+        298,
+        14
+    )])));
+
+    // earlier, but "05.68Z" sorts after "05.6834567Z" as text
+    common::update(
+        &mut analysis,
+        car_data(json!([entry("2024-03-02T15:04:05.68Z", 297, 14)])),
+        Some("2024-03-02T15:04:06.010Z"),
+    );
+
+    let latest = analysis.telemetry.driver("1").unwrap();
+    assert_eq!(latest.utc.as_deref(), Some("2024-03-02T15:04:05.6834567Z"));
+
+    // later with fewer digits and without a zone
+    common::update(
+        &mut analysis,
+        car_data(json!([entry("2024-03-02T15:04:05.7", 301, 14)])),
+        Some("2024-03-02T15:04:06.210Z"),
+    );
+
+    assert_eq!(analysis.telemetry.driver("1").unwrap().speed, Some(301));
+}
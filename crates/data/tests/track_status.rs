@@ -0,0 +1,138 @@
+mod common;
+
+use data::analysis::{track_status::NeutralisationKind, Analysis};
+use serde_json::{json, Value};
+
+fn session(status: &str, message: &str) -> Value {
+    json!({
+        "TrackStatus": { "Status": status, "Message": message },
+        "LapCount": { "CurrentLap": 10, "TotalLaps": 57 }
+    })
+}
+
+fn status(analysis: &mut Analysis, status: &str, message: &str, timestamp: &str) {
+    let update = json!({ "TrackStatus": { "Status": status, "Message": message } });
+    common::update(analysis, update, Some(timestamp));
+}
+
+fn lap(analysis: &mut Analysis, lap: i64) {
+    common::update(analysis, json!({ "LapCount": { "CurrentLap": lap } }), None);
+}
+
+#[test]
+fn records_every_change_with_its_lap() {
+    let mut analysis = common::initial(session("1", "AllClear"));
+
+    status(&mut analysis, "2", "Yellow", "2024-03-02T15:20:00.000Z");
+    // sent again with the next keyframe
+    status(&mut analysis, "2", "Yellow", "2024-03-02T15:20:05.000Z");
+    lap(&mut analysis, 11);
+    status(&mut analysis, "1", "AllClear", "2024-03-02T15:20:42.500Z");
+
+    let changes: Vec<_> = analysis
+        .track_status
+        .changes
+        .iter()
+        .map(|change| (change.status.as_str(), change.utc.as_deref(), change.lap))
+        .collect();
+
+    assert_eq!(
+        changes,
+        [
+            ("1", None, Some(10)),
+            ("2", Some("2024-03-02T15:20:00.000Z"), Some(10)),
+            ("1", Some("2024-03-02T15:20:42.500Z"), Some(11)),
+        ]
+    );
+
+    let yellow = &analysis.track_status.neutralisations[0];
+    assert_eq!(yellow.kind, NeutralisationKind::Yellow);
+    assert_eq!((yellow.start_lap, yellow.end_lap), (Some(10), Some(11)));
+    assert_eq!(yellow.duration, Some(42.5));
+    assert!(!yellow.ongoing);
+}
+
+#[test]
+fn safety_car_into_red_flag() {
+    let mut analysis = common::initial(session("1", "AllClear"));
+
+    status(&mut analysis, "4", "SCDeployed", "2024-03-02T15:20:00.000Z");
+    lap(&mut analysis, 12);
+    status(&mut analysis, "5", "Red", "2024-03-02T15:23:00.000Z");
+    // the restart is behind the safety car
+    status(&mut analysis, "4", "SCDeployed", "2024-03-02T15:50:00.000Z");
+
+    let neutralisations: Vec<_> = analysis
+        .track_status
+        .neutralisations
+        .iter()
+        .map(|neutralisation| {
+            (
+                neutralisation.kind,
+                neutralisation.duration,
+                neutralisation.ongoing,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        neutralisations,
+        [
+            (NeutralisationKind::SafetyCar, Some(180.0), false),
+            (NeutralisationKind::RedFlag, Some(1620.0), false),
+            (NeutralisationKind::SafetyCar, None, true),
+        ]
+    );
+
+    assert_eq!(
+        analysis.track_status.total(NeutralisationKind::SafetyCar),
+        180.0
+    );
+}
+
+#[test]
+fn virtual_safety_car_ending_is_one_period() {
+    let mut analysis = common::initial(session("1", "AllClear"));
+
+    status(
+        &mut analysis,
+        "6",
+        "VSCDeployed",
+        "2024-03-02T15:20:00.000Z",
+    );
+    status(&mut analysis, "7", "VSCEnding", "2024-03-02T15:21:30.000Z");
+    status(&mut analysis, "1", "AllClear", "2024-03-02T15:21:40.000Z");
+
+    assert_eq!(analysis.track_status.changes.len(), 4);
+    assert_eq!(analysis.track_status.neutralisations.len(), 1);
+
+    let vsc = &analysis.track_status.neutralisations[0];
+    assert_eq!(vsc.kind, NeutralisationKind::VirtualSafetyCar);
+    assert_eq!(vsc.duration, Some(100.0));
+}
+
+#[test]
+fn joining_during_a_neutralisation() {
+    let mut analysis = common::initial(session("4", "SCDeployed"));
+
+    // a reconnect sends the same status again
+    let again = common::transformed(session("4", "SCDeployed"));
+    analysis.initial(&again).unwrap();
+    status(&mut analysis, "1", "AllClear", "2024-03-02T15:20:00.000Z");
+
+    let value = serde_json::to_value(&analysis.track_status).unwrap();
+
+    assert_eq!(
+        value["neutralisations"],
+        json!([{
+            "kind": "safetyCar",
+            "start": null,
+            "end": "2024-03-02T15:20:00.000Z",
+            "startLap": 10,
+            "endLap": 10,
+            "duration": null,
+            "ongoing": false
+        }])
+    );
+    assert_eq!(value["changes"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,172 @@
+use data::transformer::{self, Casing, Transformer};
+use serde_json::{json, Value};
+
+fn timing_stats() -> Value {
+    json!({
+        "TimingStats": {
+            "Withheld": false,
+            "Lines": {
+                "1": {
+                    "Line": 1,
+                    "RacingNumber": "1",
+                    "PersonalBestLapTime": { "Value": "1:32.608", "Lap": 39, "Position": 1 },
+                    "BestSectors": [{ "Value": "28.910", "Position": 1 }],
+                    "BestSpeeds": {
+                        "I1": { "Value": "230", "Position": 7 },
+                        "ST": { "Value": "318", "Position": 4 }
+                    }
+                }
+            }
+        },
+        "_kf": true
+    })
+}
+
+#[test]
+fn camel_cases_like_before() {
+    let transformer = Transformer::new(Casing::CamelCase);
+
+    let mut value = timing_stats();
+    transformer.transform(&mut value);
+
+    let mut expected = timing_stats();
+    transformer::transform(&mut expected);
+
+    assert_eq!(value, expected);
+    assert_eq!(
+        value["timingStats"]["lines"]["1"]["bestSpeeds"]["st"]["value"],
+        "318"
+    );
+    assert!(value.get("_kf").is_none());
+}
+
+#[test]
+fn snake_cases() {
+    let transformer = Transformer::new(Casing::SnakeCase);
+
+    let mut value = timing_stats();
+    transformer.transform(&mut value);
+
+    let line = &value["timing_stats"]["lines"]["1"];
+    assert_eq!(line["racing_number"], "1");
+    assert_eq!(line["personal_best_lap_time"]["lap"], 39);
+    assert_eq!(line["best_speeds"]["i1"]["position"], 7);
+}
+
+#[test]
+fn casings_by_name() {
+    assert_eq!(Casing::from_name("camelCase"), Some(Casing::CamelCase));
+    assert_eq!(Casing::from_name("snake_case"), Some(Casing::SnakeCase));
+    assert_eq!(Casing::from_name("original"), Some(Casing::Original));
+    assert_eq!(Casing::from_name("kebab-case"), None);
+}
+
+#[test]
+fn inverse_restores_f1_names() {
+    for casing in [Casing::CamelCase, Casing::SnakeCase] {
+        let transformer = Transformer::new(casing).keep_keyframes(true);
+
+        let mut value = timing_stats();
+        transformer.transform(&mut value);
+        assert_ne!(value, timing_stats());
+
+        transformer.inverse(&mut value);
+        assert_eq!(value, timing_stats(), "{:?} did not round trip", casing);
+    }
+}
+
+#[test]
+fn inverse_guesses_unseen_keys() {
+    let transformer = Transformer::new(Casing::CamelCase);
+
+    let mut value = json!({ "lapCount": { "currentLap": 12, "totalLaps": 57 } });
+    transformer.inverse(&mut value);
+
+    assert_eq!(
+        value,
+        json!({ "LapCount": { "CurrentLap": 12, "TotalLaps": 57 } })
+    );
+}
+
+#[test]
+fn outputs_another_casing() {
+    let transformer = Transformer::new(Casing::CamelCase).output_casing(Casing::SnakeCase);
+
+    let mut value = timing_stats();
+    transformer.transform(&mut value);
+    assert_eq!(value["timingStats"]["lines"]["1"]["racingNumber"], "1");
+
+    let mut output = value.clone();
+    transformer.output(&mut output);
+
+    let line = &output["timing_stats"]["lines"]["1"];
+    assert_eq!(line["racing_number"], "1");
+    assert_eq!(line["best_speeds"]["st"]["value"], "318");
+
+    // without an output casing the transformed data goes out as it is
+    let transformer = Transformer::new(Casing::CamelCase);
+    transformer.transform(&mut timing_stats());
+
+    let mut output = value.clone();
+    transformer.output(&mut output);
+    assert_eq!(output, value);
+}
+
+#[test]
+fn keeps_original_names() {
+    let transformer = Transformer::new(Casing::Original);
+
+    let mut value = timing_stats();
+    transformer.transform(&mut value);
+
+    let mut expected = timing_stats();
+    expected.as_object_mut().unwrap().remove("_kf");
+
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn renames_deleted_keys() {
+    let transformer = Transformer::new(Casing::SnakeCase);
+
+    let mut value =
+        json!({ "TimingStats": { "Lines": { "1": { "_deleted": ["PersonalBestLapTime"] } } } });
+    transformer.transform(&mut value);
+
+    assert_eq!(
+        value,
+        json!({ "timing_stats": { "lines": { "1": { "_deleted": ["personal_best_lap_time"] } } } })
+    );
+
+    transformer.inverse(&mut value);
+    assert_eq!(
+        value["TimingStats"]["Lines"]["1"]["_deleted"],
+        json!(["PersonalBestLapTime"])
+    );
+}
+
+#[test]
+fn same_result_from_the_cache() {
+    let transformer = Transformer::new(Casing::CamelCase);
+
+    let mut first = timing_stats();
+    transformer.transform(&mut first);
+
+    // every key is a cache hit the second time
+    let mut second = timing_stats();
+    transformer.transform(&mut second);
+
+    assert_eq!(first, second);
+
+    let mut car_data = json!({
+        "CarData": { "Entries": [{ "Utc": "2024-03-02T15:04:05.123Z", "Cars": { "1": { "Channels": { "0": 11210, "2": 287, "45": 12 } } } }] }
+    });
+    transformer.transform(&mut car_data);
+
+    assert_eq!(
+        car_data,
+        json!({
+            "carData": { "entries": [{ "utc": "2024-03-02T15:04:05.123Z", "cars": { "1": { "channels": { "0": 11210, "2": 287, "45": 12 } } } }] }
+        })
+    );
+}
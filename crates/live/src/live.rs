@@ -0,0 +1,33 @@
+use std::sync::{Arc, Mutex};
+
+use data::analysis::Analysis;
+use serde_json::Value;
+
+pub mod server;
+pub mod state;
+
+pub type LiveState = Arc<Mutex<Value>>;
+/// what is derived from the feed next to the state, like the lap history
+pub type LiveAnalysis = Arc<Mutex<Analysis>>;
+
+#[derive(Clone)]
+pub enum LiveEvent {
+    Initial(String),
+    Update(String),
+}
+
+impl LiveEvent {
+    pub fn name(&self) -> &str {
+        match self {
+            LiveEvent::Initial(_) => "initial",
+            LiveEvent::Update(_) => "update",
+        }
+    }
+
+    pub fn inner(self) -> String {
+        match self {
+            LiveEvent::Initial(v) => v,
+            LiveEvent::Update(v) => v,
+        }
+    }
+}
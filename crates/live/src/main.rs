@@ -1,37 +1,14 @@
 use std::sync::{Arc, Mutex};
 
-use serde_json::{json, Value};
+use data::{
+    analysis::Analysis,
+    transformer::{Casing, Transformer},
+};
+use live::{server, state, LiveEvent};
+use serde_json::json;
 use tokio::sync::broadcast;
 
-mod server;
-mod state;
-
-use env;
-use tracing::level_filters::LevelFilter;
-
-type LiveState = Arc<Mutex<Value>>;
-
-#[derive(Clone)]
-pub enum LiveEvent {
-    Initial(String),
-    Update(String),
-}
-
-impl LiveEvent {
-    pub fn name(&self) -> &str {
-        match self {
-            LiveEvent::Initial(_) => "initial",
-            LiveEvent::Update(_) => "update",
-        }
-    }
-
-    pub fn inner(self) -> String {
-        match self {
-            LiveEvent::Initial(v) => v,
-            LiveEvent::Update(v) => v,
-        }
-    }
-}
+use tracing::{level_filters::LevelFilter, warn};
 
 #[tokio::main]
 async fn main() {
@@ -40,14 +17,34 @@ async fn main() {
 
     let (tx, _rx) = broadcast::channel::<LiveEvent>(10);
     let state = Arc::new(Mutex::new(json!({})));
-
-    state::manage(tx.clone(), state.clone());
-
-    server::init(tx, state)
+    let analysis = Arc::new(Mutex::new(Analysis::default()));
+    // the state and the analysis rely on camelCase, other casings are only for the events
+    let transformer =
+        Arc::new(Transformer::new(Casing::CamelCase).output_casing(casing_from_env()));
+
+    state::manage(
+        tx.clone(),
+        state.clone(),
+        analysis.clone(),
+        transformer.clone(),
+    );
+
+    server::init(tx, state, analysis, transformer)
         .await
         .expect("http server setup failed");
 }
 
+/// reads `TRANSFORM_CASING`, one of "camelCase", "snake_case" or "original"
+fn casing_from_env() -> Casing {
+    match std::env::var("TRANSFORM_CASING") {
+        Ok(name) => Casing::from_name(&name).unwrap_or_else(|| {
+            warn!("unknown casing '{}', using camelCase", name);
+            Casing::CamelCase
+        }),
+        Err(_) => Casing::CamelCase,
+    }
+}
+
 fn init_logs() {
     let env_filter = tracing_subscriber::EnvFilter::builder()
         .with_default_directive(LevelFilter::INFO.into())
@@ -1,22 +1,34 @@
 use std::{error::Error, net::SocketAddr, sync::Arc, thread, time::Duration};
 
 use axum::{routing::get, Router};
+use data::transformer::Transformer;
 use tokio::sync::broadcast;
 use tower_governor::{
     governor::GovernorConfigBuilder, key_extractor::SmartIpKeyExtractor, GovernorLayer,
 };
 use tracing::info;
 
-use crate::{LiveEvent, LiveState};
+use crate::{LiveAnalysis, LiveEvent, LiveState};
 
 mod cors;
 mod drivers;
+mod gaps;
 mod health;
+mod laps;
 pub mod live;
+mod pit_stops;
+mod race_control;
+mod raw;
+mod stints;
+mod telemetry;
+mod track_status;
 
 pub struct AppState {
     tx: broadcast::Sender<LiveEvent>,
     state: LiveState,
+    analysis: LiveAnalysis,
+    /// the one the state was transformed with, knows the original f1 names
+    transformer: Arc<Transformer>,
 }
 
 fn addr() -> String {
@@ -28,6 +40,8 @@ const CLEANUP_INTERVAL: u64 = 60;
 pub async fn init(
     tx: broadcast::Sender<LiveEvent>,
     state: LiveState,
+    analysis: LiveAnalysis,
+    transformer: Arc<Transformer>,
 ) -> Result<(), Box<dyn Error>> {
     let cors = cors::init();
 
@@ -53,12 +67,36 @@ pub async fn init(
         config: governor_conf,
     };
 
-    let app_state = Arc::new(AppState { tx, state });
+    let app_state = Arc::new(AppState {
+        tx,
+        state,
+        analysis,
+        transformer,
+    });
 
     let app = Router::new()
         .route("/api/sse", get(live::sse_handler))
         .route("/api/health", get(health::check))
         .route("/api/drivers", get(drivers::get_drivers))
+        .route("/api/state/raw", get(raw::get_raw_state))
+        .route("/api/drs-trains", get(gaps::get_drs_trains))
+        .route("/api/gaps", get(gaps::get_gaps))
+        .route("/api/gaps/:racing_number", get(gaps::get_driver_gaps))
+        .route("/api/laps", get(laps::get_laps))
+        .route("/api/laps/:racing_number", get(laps::get_driver_laps))
+        .route("/api/pit-stops", get(pit_stops::get_pit_stops))
+        .route(
+            "/api/pit-stops/:racing_number",
+            get(pit_stops::get_driver_pit_stops),
+        )
+        .route("/api/race-control", get(race_control::get_race_control))
+        .route("/api/stints", get(stints::get_stints))
+        .route("/api/telemetry", get(telemetry::get_telemetry))
+        .route(
+            "/api/telemetry/:racing_number",
+            get(telemetry::get_driver_telemetry),
+        )
+        .route("/api/track-status", get(track_status::get_track_status))
         .layer(cors)
         .layer(governor)
         .with_state(app_state)
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use data::analysis::gaps::{DrsTrain, GapHistory, GapSample};
+
+use super::AppState;
+
+/// the gaps of every driver at every sector, keyed by racing number
+pub async fn get_gaps(State(state): State<Arc<AppState>>) -> Json<GapHistory> {
+    let gaps = state.analysis.lock().unwrap().gaps.clone();
+    Json(gaps)
+}
+
+pub async fn get_driver_gaps(
+    State(state): State<Arc<AppState>>,
+    Path(racing_number): Path<String>,
+) -> Result<Json<Vec<GapSample>>, StatusCode> {
+    let analysis = state.analysis.lock().unwrap();
+
+    match analysis.gaps.driver(&racing_number) {
+        Some(samples) => Ok(Json(samples.to_vec())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// the drs trains of every lap so far
+pub async fn get_drs_trains(State(state): State<Arc<AppState>>) -> Json<Vec<DrsTrain>> {
+    let trains = state.analysis.lock().unwrap().gaps.drs_trains();
+    Json(trains)
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use data::analysis::laps::{Lap, LapHistory};
+
+use super::AppState;
+
+/// the finished laps of every driver, keyed by racing number
+pub async fn get_laps(State(state): State<Arc<AppState>>) -> Json<LapHistory> {
+    let laps = state.analysis.lock().unwrap().laps.clone();
+    Json(laps)
+}
+
+pub async fn get_driver_laps(
+    State(state): State<Arc<AppState>>,
+    Path(racing_number): Path<String>,
+) -> Result<Json<Vec<Lap>>, StatusCode> {
+    let analysis = state.analysis.lock().unwrap();
+
+    match analysis.laps.driver(&racing_number) {
+        Some(laps) => Ok(Json(laps.to_vec())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
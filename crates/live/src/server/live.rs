@@ -21,9 +21,10 @@ pub async fn sse_handler(
     info!("connections: {}", state.tx.receiver_count());
 
     let initial_stream = futures::stream::once(async {
-        let initial_state = state.state.lock().unwrap().to_string();
+        let mut initial_state = state.state.lock().unwrap().clone();
+        state.transformer.output(&mut initial_state);
         mem::drop(state);
-        let initial = compression::deflate(initial_state).unwrap();
+        let initial = compression::deflate(initial_state.to_string()).unwrap();
 
         debug!("streaming current initial");
 
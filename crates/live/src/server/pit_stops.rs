@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use data::analysis::pit_stops::{PitStop, PitStops};
+
+use super::AppState;
+
+/// the pit stops of every driver, keyed by racing number
+pub async fn get_pit_stops(State(state): State<Arc<AppState>>) -> Json<PitStops> {
+    let pit_stops = state.analysis.lock().unwrap().pit_stops.clone();
+    Json(pit_stops)
+}
+
+pub async fn get_driver_pit_stops(
+    State(state): State<Arc<AppState>>,
+    Path(racing_number): Path<String>,
+) -> Result<Json<Vec<PitStop>>, StatusCode> {
+    let analysis = state.analysis.lock().unwrap();
+
+    match analysis.pit_stops.driver(&racing_number) {
+        Some(pit_stops) => Ok(Json(pit_stops.to_vec())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
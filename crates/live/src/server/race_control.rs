@@ -0,0 +1,31 @@
+use std::{mem, sync::Arc};
+
+use axum::{extract::State, http::StatusCode, Json};
+use tracing::error;
+
+use data::{analysis::race_control::RaceControl, models::RaceControlMessages};
+
+use super::AppState;
+
+/// the race control messages and the event each of them is about
+pub async fn get_race_control(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RaceControl>, StatusCode> {
+    let live_state = state.state.lock().unwrap().clone();
+    mem::drop(state);
+
+    let Some(messages) = live_state.pointer("/raceControlMessages") else {
+        return Ok(Json(RaceControl::default()));
+    };
+
+    match serde_json::from_value::<RaceControlMessages>(messages.clone()) {
+        Ok(messages) => Ok(Json(RaceControl::new(&messages))),
+        Err(e) => {
+            error!(
+                "failed to parse race control messages from live state: {}",
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use std::{mem, sync::Arc};
+
+use axum::{extract::State, Json};
+use serde_json::Value;
+
+use super::AppState;
+
+/// the current state with the original f1 names, for tools that expect the raw feed schema
+pub async fn get_raw_state(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let mut live_state = state.state.lock().unwrap().clone();
+    let transformer = state.transformer.clone();
+    mem::drop(state);
+
+    transformer.inverse(&mut live_state);
+
+    Json(live_state)
+}
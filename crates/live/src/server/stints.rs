@@ -0,0 +1,36 @@
+use std::{mem, sync::Arc};
+
+use axum::{extract::State, http::StatusCode, Json};
+use tracing::error;
+
+use data::{
+    analysis::stints::Stints,
+    models::{SessionInfo, TimingAppData},
+};
+
+use super::AppState;
+
+/// the stints of every driver and who still has to use a second compound
+pub async fn get_stints(State(state): State<Arc<AppState>>) -> Result<Json<Stints>, StatusCode> {
+    let live_state = state.state.lock().unwrap().clone();
+    mem::drop(state);
+
+    // nothing yet before the first initial or in sessions without timing app data
+    let Some(timing_app_data) = live_state.pointer("/timingAppData") else {
+        return Ok(Json(Stints::default()));
+    };
+
+    let timing_app_data = match serde_json::from_value::<TimingAppData>(timing_app_data.clone()) {
+        Ok(timing_app_data) => timing_app_data,
+        Err(e) => {
+            error!("failed to parse timing app data from live state: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let session_info = live_state
+        .pointer("/sessionInfo")
+        .and_then(|info| serde_json::from_value::<SessionInfo>(info.clone()).ok());
+
+    Ok(Json(Stints::new(&timing_app_data, session_info.as_ref())))
+}
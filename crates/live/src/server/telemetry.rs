@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use data::{analysis::telemetry::LatestTelemetry, models::Telemetry};
+
+use super::AppState;
+
+/// the latest car data sample of every driver, keyed by racing number
+pub async fn get_telemetry(State(state): State<Arc<AppState>>) -> Json<LatestTelemetry> {
+    let telemetry = state.analysis.lock().unwrap().telemetry.clone();
+    Json(telemetry)
+}
+
+pub async fn get_driver_telemetry(
+    State(state): State<Arc<AppState>>,
+    Path(racing_number): Path<String>,
+) -> Result<Json<Telemetry>, StatusCode> {
+    let analysis = state.analysis.lock().unwrap();
+
+    match analysis.telemetry.driver(&racing_number) {
+        Some(telemetry) => Ok(Json(telemetry.clone())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use data::analysis::track_status::TrackStatusTimeline;
+
+use super::AppState;
+
+/// every track status of the session and the neutralisations
+pub async fn get_track_status(State(state): State<Arc<AppState>>) -> Json<TrackStatusTimeline> {
+    let track_status = state.analysis.lock().unwrap().track_status.clone();
+    Json(track_status)
+}
@@ -1,85 +1,182 @@
-use std::{mem, thread, time::Duration};
+use std::{
+    mem,
+    sync::{Arc, LazyLock},
+    thread,
+    time::Duration,
+};
 
 use futures::{pin_mut, Stream};
+use serde_json::{Map, Value};
 use tokio::{sync::broadcast::Sender, time::sleep};
 use tokio_stream::StreamExt;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 
-use crate::{LiveEvent, LiveState};
+use crate::{LiveAnalysis, LiveEvent, LiveState};
 
-use client;
-use data::{compression, merge::merge, transformer};
-
-pub fn manage(tx: Sender<LiveEvent>, state: LiveState) {
-    // TODO start and stop on connect and disconnect
+use data::{
+    compression,
+    merge::{merge_with, Strategies},
+    transformer::Transformer,
+};
 
+pub fn manage(
+    tx: Sender<LiveEvent>,
+    state: LiveState,
+    analysis: LiveAnalysis,
+    transformer: Arc<Transformer>,
+) {
     thread::spawn(|| {
         let rt = tokio::runtime::Runtime::new().unwrap();
 
         rt.block_on(async {
-            keep_client_alive(tx, state).await;
+            keep_client_alive(tx, state, analysis, transformer).await;
         })
     });
 }
 
-async fn keep_client_alive(tx: Sender<LiveEvent>, state: LiveState) {
+async fn keep_client_alive(
+    tx: Sender<LiveEvent>,
+    state: LiveState,
+    analysis: LiveAnalysis,
+    transformer: Arc<Transformer>,
+) {
+    let mut source = match client::feed::from_env() {
+        Ok(source) => source,
+        Err(e) => {
+            error!("failed to set up the feed source: {}", e);
+            return;
+        }
+    };
+
+    // the analysis endpoints need every update of the session, not just those while a browser watched
+    let on_demand = std::env::var("FEED_ON_DEMAND").is_ok_and(|on_demand| on_demand == "true");
+
+    let mut failed_delay = MIN_FAILED_DELAY;
+
     loop {
-        if tx.receiver_count() < 2 {
+        // main keeps a receiver of its own, browsers come on top of it
+        if on_demand && tx.receiver_count() < 2 {
             debug!("no connections yet");
             sleep(Duration::from_secs(5)).await;
             continue;
         }
 
-        info!("starting client...");
-
-        let stream = client::init().await;
-
-        let stream = match stream {
+        let stream = match source.open().await {
             Ok(stream) => stream,
             Err(e) => {
-                error!("client setup failed, restarting in 5 seconds {}", e);
-                sleep(Duration::from_secs(5)).await;
+                let delay = retry_delay(&e);
+                error!("client setup failed, restarting in {:?} {}", delay, e);
+                sleep(delay).await;
                 continue;
             }
         };
 
-        let parsed_stream = client::parse_stream(stream).await;
+        let mut delivered = false;
+        let stream = stream.map(|message| {
+            if let Ok(client::message::Message::Initial(_) | client::message::Message::Updates(_)) =
+                message
+            {
+                delivered = true;
+            }
+            message
+        });
 
-        handle_stream(parsed_stream, tx.clone(), state.clone()).await;
+        let end = handle_stream(stream, tx.clone(), state.clone(), &analysis, &transformer).await;
+
+        if delivered {
+            failed_delay = MIN_FAILED_DELAY;
+        }
+
+        match end {
+            StreamEnd::Closed => debug!("stream closed, resuming next"),
+            StreamEnd::SessionChanged => {
+                analysis.lock().unwrap().reset();
+                source.reset();
+            }
+            // a hub that rejects every subscribe must not be negotiated with in a tight loop
+            StreamEnd::Failed => {
+                source.reset();
+                warn!("stream failed, connecting again in {:?}", failed_delay);
+                sleep(failed_delay).await;
+                failed_delay = (failed_delay * 2).min(MAX_FAILED_DELAY);
+            }
+        }
     }
 }
 
-async fn handle_stream(
-    stream: impl Stream<Item = client::message::Message>,
+/// the wait after the first failed stream, doubled with every failure until a stream delivers data
+const MIN_FAILED_DELAY: Duration = Duration::from_secs(1);
+const MAX_FAILED_DELAY: Duration = Duration::from_secs(60);
+
+fn retry_delay(error: &client::Error) -> Duration {
+    match error {
+        // f1 only hands out tokens and cookies while a session is on, no need to hammer them
+        client::Error::MissingCookie | client::Error::MissingToken => Duration::from_secs(30),
+        e if e.is_transient() => Duration::from_secs(5),
+        _ => Duration::from_secs(15),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// the connection dropped, we can try to resume where we left off
+    Closed,
+    SessionChanged,
+    /// the hub rejected us, only a fresh connection helps
+    Failed,
+}
+
+/// feeds the messages of one stream into the state and analysis and forwards them as events
+pub async fn handle_stream(
+    stream: impl Stream<Item = Result<client::message::Message, client::Error>>,
     tx: Sender<LiveEvent>,
     state: LiveState,
-) {
+    analysis: &LiveAnalysis,
+    transformer: &Transformer,
+) -> StreamEnd {
     pin_mut!(stream);
 
     while let Some(message) = stream.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("client stream failed, reconnecting: {}", e);
+                return StreamEnd::Closed;
+            }
+        };
+
         match message {
-            client::message::Message::Updates(mut updates) => {
+            client::message::Message::Updates(updates) => {
                 trace!("recived update");
 
                 let mut state = state.lock().unwrap();
 
-                for update in updates.iter_mut() {
-                    let update = transformer::transform_map(update);
+                for update in updates {
+                    let timestamp = update.timestamp.clone();
+                    let update = transformer.transform_map(&mut update.into_map());
 
                     if let Some(new_session_name) = update.pointer("/sessionInfo/name") {
-                        let current_session_name = state
+                        // a recording can start with updates before any initial,
+                        // without a current name there is no session yet to change from
+                        let changed = state
                             .pointer("/sessionInfo/name")
-                            .expect("we always should have a session name");
+                            .is_some_and(|current| current != new_session_name);
 
-                        if new_session_name != current_session_name {
+                        if changed {
                             info!("session name changed, restarting client");
-                            return;
+                            return StreamEnd::SessionChanged;
                         }
                     }
 
-                    let Some(update_compressed) = compression::deflate(update.to_string()) else {
-                        error!("failed compressing update");
-                        continue;
+                    let mut event = with_timestamp(&update, timestamp.as_deref());
+                    transformer.output(&mut event["update"]);
+
+                    let update_compressed = match compression::deflate(event.to_string()) {
+                        Ok(compressed) => compressed,
+                        Err(e) => {
+                            error!("failed compressing update: {}", e);
+                            continue;
+                        }
                     };
 
                     trace!("update compressed='{}'", update_compressed);
@@ -89,7 +186,15 @@ async fn handle_stream(
                         Err(e) => error!("failed sending update: {}", e),
                     };
 
-                    merge(&mut state, update)
+                    if let Err(e) = analysis
+                        .lock()
+                        .unwrap()
+                        .update(&update, timestamp.as_deref())
+                    {
+                        warn!("failed to analyse update: {}", e);
+                    }
+
+                    merge_update(&mut state, update)
                 }
 
                 mem::drop(state);
@@ -97,15 +202,24 @@ async fn handle_stream(
             client::message::Message::Initial(mut initial) => {
                 trace!("recived initial");
 
-                transformer::transform(&mut initial);
+                transformer.transform(&mut initial);
+
+                if let Err(e) = analysis.lock().unwrap().initial(&initial) {
+                    warn!("failed to analyse initial: {}", e);
+                }
 
                 let mut state = state.lock().unwrap();
                 *state = initial.clone();
                 mem::drop(state);
 
-                let Some(initial) = compression::deflate(initial.to_string()) else {
-                    error!("failed compressing update");
-                    continue;
+                transformer.output(&mut initial);
+
+                let initial = match compression::deflate(initial.to_string()) {
+                    Ok(compressed) => compressed,
+                    Err(e) => {
+                        error!("failed compressing initial: {}", e);
+                        continue;
+                    }
                 };
 
                 trace!("initial compressed='{}'", initial);
@@ -115,6 +229,45 @@ async fn handle_stream(
                     Err(e) => error!("failed sending initial: {}", e),
                 };
             }
+            client::message::Message::Init => debug!("client initialized"),
+            client::message::Message::KeepAlive => trace!("recived keep-alive"),
+            client::message::Message::Result { id } => debug!("invocation {} finished", id),
+            client::message::Message::Error { id, error, .. } => {
+                error!("hub error for invocation {:?}: {}", id, error);
+                return StreamEnd::Failed;
+            }
+            client::message::Message::Reconnect => {
+                info!("hub asked us to reconnect");
+                return StreamEnd::Closed;
+            }
+            client::message::Message::Disconnect => {
+                info!("hub asked us to disconnect, restarting client");
+                return StreamEnd::Failed;
+            }
+            client::message::Message::Unknown(msg) => debug!("unhandled message {}", msg),
         }
     }
+
+    StreamEnd::Closed
+}
+
+// the upstream timestamp is only sent along with the update, it never becomes part of the state,
+// so it sits next to the update instead of between the topics
+fn with_timestamp(update: &Value, timestamp: Option<&str>) -> Value {
+    let mut event = Map::new();
+
+    if let Some(timestamp) = timestamp {
+        event.insert("timestamp".to_owned(), Value::String(timestamp.to_owned()));
+    }
+
+    event.insert("update".to_owned(), update.clone());
+
+    Value::Object(event)
+}
+
+// how every topic merges, carData and position for example are replaced instead of growing forever
+static STRATEGIES: LazyLock<Strategies> = LazyLock::new(Strategies::feed);
+
+fn merge_update(state: &mut Value, update: Value) {
+    merge_with(state, update, &STRATEGIES);
 }
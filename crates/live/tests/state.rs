@@ -0,0 +1,239 @@
+use std::sync::{Arc, Mutex};
+
+use client::{
+    feed::{self, FeedSource, FeedStream},
+    message::{Message, Update},
+};
+use data::{
+    analysis::Analysis,
+    compression,
+    transformer::{Casing, Transformer},
+};
+use live::{
+    state::{handle_stream, StreamEnd},
+    LiveAnalysis, LiveEvent, LiveState,
+};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc};
+
+struct Live {
+    state: LiveState,
+    analysis: LiveAnalysis,
+    tx: broadcast::Sender<LiveEvent>,
+    rx: broadcast::Receiver<LiveEvent>,
+    transformer: Transformer,
+}
+
+impl Live {
+    fn new() -> Self {
+        let (tx, rx) = broadcast::channel(16);
+
+        Live {
+            state: Arc::new(Mutex::new(json!({}))),
+            analysis: Arc::new(Mutex::new(Analysis::default())),
+            tx,
+            rx,
+            transformer: Transformer::new(Casing::CamelCase),
+        }
+    }
+
+    fn with_output(casing: Casing) -> Self {
+        Live {
+            transformer: Transformer::new(Casing::CamelCase).output_casing(casing),
+            ..Live::new()
+        }
+    }
+
+    async fn handle(&self, stream: FeedStream) -> StreamEnd {
+        handle_stream(
+            stream,
+            self.tx.clone(),
+            self.state.clone(),
+            &self.analysis,
+            &self.transformer,
+        )
+        .await
+    }
+
+    fn state(&self) -> Value {
+        self.state.lock().unwrap().clone()
+    }
+
+    // the events as the browsers get them, inflated
+    fn events(&mut self) -> Vec<(String, Value)> {
+        let mut events = Vec::new();
+
+        while let Ok(event) = self.rx.try_recv() {
+            let name = event.name().to_owned();
+            events.push((name, compression::inflate(&event.inner()).unwrap()));
+        }
+
+        events
+    }
+}
+
+async fn source() -> (mpsc::Sender<Message>, FeedStream) {
+    let (feed, mut source) = feed::channel(16);
+    let stream = source.open().await.unwrap();
+    (feed, stream)
+}
+
+fn update(topic: &str, data: Value, timestamp: Option<&str>) -> Message {
+    Message::Updates(vec![Update {
+        topic: topic.to_owned(),
+        data,
+        timestamp: timestamp.map(str::to_owned),
+    }])
+}
+
+fn initial() -> Message {
+    Message::Initial(json!({
+        "SessionInfo": { "Name": "Race", "Type": "Race" },
+        "TrackStatus": { "Status": "1", "Message": "AllClear" },
+        "LapCount": { "CurrentLap": 12, "TotalLaps": 57 }
+    }))
+}
+
+#[tokio::test]
+async fn ends_when_the_session_changes() {
+    let mut live = Live::new();
+    let (feed, stream) = source().await;
+
+    feed.send(Message::Init).await.unwrap();
+    feed.send(initial()).await.unwrap();
+    feed.send(update(
+        "TrackStatus",
+        json!({ "Status": "4", "Message": "SCDeployed" }),
+        Some("2024-03-02T15:20:00.000Z"),
+    ))
+    .await
+    .unwrap();
+    feed.send(update("SessionInfo", json!({ "Name": "Qualifying" }), None))
+        .await
+        .unwrap();
+    // never looked at, the session is over
+    feed.send(update("LapCount", json!({ "CurrentLap": 13 }), None))
+        .await
+        .unwrap();
+
+    assert_eq!(live.handle(stream).await, StreamEnd::SessionChanged);
+
+    let state = live.state();
+    assert_eq!(state["sessionInfo"]["name"], "Race");
+    assert_eq!(state["trackStatus"]["status"], "4");
+    assert_eq!(state["lapCount"]["currentLap"], 12);
+
+    let track_status = live.analysis.lock().unwrap().track_status.clone();
+    assert_eq!(track_status.changes.len(), 2);
+    assert_eq!(track_status.neutralisations[0].start_lap, Some(12));
+
+    let events = live.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, "initial");
+    assert_eq!(events[0].1["trackStatus"]["status"], "1");
+    assert_eq!(events[1].0, "update");
+    assert_eq!(
+        events[1].1,
+        json!({
+            "timestamp": "2024-03-02T15:20:00.000Z",
+            "update": { "trackStatus": { "status": "4", "message": "SCDeployed" } }
+        })
+    );
+}
+
+#[tokio::test]
+async fn only_the_events_in_the_output_casing() {
+    let mut live = Live::with_output(Casing::SnakeCase);
+    let (feed, stream) = source().await;
+
+    feed.send(initial()).await.unwrap();
+    feed.send(update(
+        "LapCount",
+        json!({ "CurrentLap": 13 }),
+        Some("2024-03-02T15:20:00.000Z"),
+    ))
+    .await
+    .unwrap();
+    feed.send(update("SessionInfo", json!({ "Name": "Qualifying" }), None))
+        .await
+        .unwrap();
+
+    // the state stays camelCase, the session change is still seen
+    assert_eq!(live.handle(stream).await, StreamEnd::SessionChanged);
+
+    let state = live.state();
+    assert_eq!(state["lapCount"]["currentLap"], 13);
+    assert_eq!(live.analysis.lock().unwrap().track_status.changes.len(), 1);
+
+    let events = live.events();
+    assert_eq!(events[0].1["session_info"]["name"], "Race");
+    assert_eq!(events[0].1["lap_count"]["total_laps"], 57);
+    assert_eq!(
+        events[1].1,
+        json!({
+            "timestamp": "2024-03-02T15:20:00.000Z",
+            "update": { "lap_count": { "current_lap": 13 } }
+        })
+    );
+}
+
+#[tokio::test]
+async fn closes_with_the_stream() {
+    let mut live = Live::new();
+    let (feed, stream) = source().await;
+
+    feed.send(initial()).await.unwrap();
+    feed.send(update("LapCount", json!({ "CurrentLap": 13 }), None))
+        .await
+        .unwrap();
+    feed.send(Message::KeepAlive).await.unwrap();
+    drop(feed);
+
+    assert_eq!(live.handle(stream).await, StreamEnd::Closed);
+
+    assert_eq!(live.state()["lapCount"]["currentLap"], 13);
+
+    // no timestamp, no timestamp key
+    let events = live.events();
+    assert_eq!(
+        events[1].1,
+        json!({ "update": { "lapCount": { "currentLap": 13 } } })
+    );
+}
+
+#[tokio::test]
+async fn updates_before_the_initial() {
+    let live = Live::new();
+    let (feed, stream) = source().await;
+
+    // a recording that starts in the middle of the session
+    feed.send(update("SessionInfo", json!({ "Name": "Race" }), None))
+        .await
+        .unwrap();
+    feed.send(update("LapCount", json!({ "CurrentLap": 20 }), None))
+        .await
+        .unwrap();
+    drop(feed);
+
+    assert_eq!(live.handle(stream).await, StreamEnd::Closed);
+
+    let state = live.state();
+    assert_eq!(state["sessionInfo"]["name"], "Race");
+    assert_eq!(state["lapCount"]["currentLap"], 20);
+}
+
+#[tokio::test]
+async fn fails_on_hub_errors() {
+    let live = Live::new();
+    let (feed, stream) = source().await;
+
+    feed.send(Message::Error {
+        id: Some("1".to_owned()),
+        error: "There was an error invoking Hub method 'streaming.Subscribe'.".to_owned(),
+        hub_exception: true,
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(live.handle(stream).await, StreamEnd::Failed);
+}
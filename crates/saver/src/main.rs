@@ -2,12 +2,14 @@ use std::{
     env,
     fs::File,
     io::{LineWriter, Write},
+    time::Duration,
 };
 
+use tokio::time::sleep;
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, level_filters::LevelFilter, warn};
 
-use client;
+const MAX_INIT_ATTEMPTS: u32 = 5;
 
 #[tokio::main]
 async fn main() {
@@ -38,19 +40,28 @@ async fn main() {
 
     info!("saving socket data to path {}", path.display());
 
-    let stream = client::init().await;
+    let subscription = client::Subscription::from_env();
 
-    let mut stream = match stream {
-        Ok(stream) => stream,
-        Err(e) => {
-            error!("failed to init client {}", e);
-            return;
+    let mut attempt = 1;
+
+    let mut stream = loop {
+        match client::init(&subscription).await {
+            Ok(stream) => break stream,
+            Err(e) if e.is_transient() && attempt < MAX_INIT_ATTEMPTS => {
+                warn!("failed to init client, retrying in 5 seconds {}", e);
+                attempt += 1;
+                sleep(Duration::from_secs(5)).await;
+            }
+            Err(e) => {
+                error!("failed to init client {}", e);
+                return;
+            }
         }
     };
 
-    while let Some(Ok(msg)) = stream.next().await {
-        match msg {
-            client::tungstenite::Message::Text(txt) => {
+    while let Some(frame) = stream.next().await {
+        match frame {
+            Ok(txt) => {
                 debug!("received message: {}", txt);
 
                 match writeln!(file, "{}", txt) {
@@ -58,11 +69,10 @@ async fn main() {
                     Err(e) => error!("failed to write message to file {}", e),
                 }
             }
-            client::tungstenite::Message::Close(_) => {
-                error!("connection got closed by server");
+            Err(e) => {
+                error!("connection failed {}", e);
                 break;
             }
-            _ => warn!("unhandled message, probably binary, ping or pong"),
         }
     }
 
@@ -65,12 +65,9 @@ async fn handle_ws(socket: WebSocket, state: Arc<AppState>) {
         } => {}
         _ = async {
             while let Some(Ok(msg)) = rx.next().await {
-                match msg {
-                    Message::Close(_) => {
-                        info!("received close");
-                        break;
-                    }
-                    _ => {}
+                if let Message::Close(_) = msg {
+                    info!("received close");
+                    break;
                 }
             }
         } => {}
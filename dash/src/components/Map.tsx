@@ -173,7 +173,7 @@ export default function Map() {
 				<>
 					{objectEntries(drivers)
 						.reverse()
-						.filter((driver) => !!positions[driver.racingNumber].X && !!positions[driver.racingNumber].Y)
+						.filter((driver) => !!positions[driver.racingNumber].x && !!positions[driver.racingNumber].y)
 						.map((driver) => {
 							const timingDriver = timingDrivers?.lines[driver.racingNumber];
 							const hidden = timingDriver
@@ -232,7 +232,7 @@ type CarDotProps = {
 };
 
 const CarDot = ({ pos, name, color, favoriteDriver, pit, hidden, rotation, centerX, centerY }: CarDotProps) => {
-	const rotatedPos = rotate(pos.X, pos.Y, rotation, centerX, centerY);
+	const rotatedPos = rotate(pos.x, pos.y, rotation, centerX, centerY);
 	const transform = [`translateX(${rotatedPos.x}px)`, `translateY(${rotatedPos.y}px)`].join(" ");
 
 	return (
@@ -44,7 +44,7 @@ export default function Driver({ driver, timingDriver, position }: Props) {
 	const timingStatsDriver = useDataStore((state) => state?.timingStats?.lines[driver.racingNumber]);
 	const appTimingDriver = useDataStore((state) => state?.timingAppData?.lines[driver.racingNumber]);
 	const carData = useCarDataStore((state) =>
-		state?.carsData ? state.carsData[driver.racingNumber].Channels : undefined,
+		state?.carsData ? state.carsData[driver.racingNumber].channels : undefined,
 	);
 
 	const hasFastest = timingStatsDriver?.personalBestLapTime.position == 1;
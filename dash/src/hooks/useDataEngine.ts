@@ -2,10 +2,9 @@
 
 import { useEffect, useRef, useState } from "react";
 
-import type { CarData, CarsData, Position, Positions, State } from "@/types/state.type";
+import type { CarsData, Positions, State } from "@/types/state.type";
 import type { MessageInitial, MessageUpdate } from "@/types/message.type";
 
-import { inflate } from "@/lib/inflate";
 import { utcToLocalMs } from "@/lib/utcToLocalMs";
 
 import { useSettingsStore } from "@/stores/useSettingsStore";
@@ -43,7 +42,7 @@ type Props = {
 
 export const useDataEngine = ({ updateState, updatePosition, updateCarData }: Props) => {
 	const buffers = bufferTypes.reduce<Buffers>((acc, type) => {
-		acc[type] = useStatefulBuffer();
+		acc[type] = useStatefulBuffer(type);
 		return acc;
 	}, {} as Buffers);
 
@@ -62,7 +61,7 @@ export const useDataEngine = ({ updateState, updatePosition, updateCarData }: Pr
 
 	const intervalRef = useRef<NodeJS.Timeout | null>(null);
 
-	const handleInitial = ({ carDataZ, positionZ, ...initial }: MessageInitial) => {
+	const handleInitial = ({ carData, position, ...initial }: MessageInitial) => {
 		// dataStore.set(initial);
 		updateState(initial);
 
@@ -72,45 +71,45 @@ export const useDataEngine = ({ updateState, updatePosition, updateCarData }: Pr
 			if (data) buffer.set(data);
 		});
 
-		if (carDataZ) {
-			const carData = inflate<CarData>(carDataZ);
-			// carDataStore.set(carData.Entries[0].Cars);
-			updateCarData(carData.Entries[0].Cars);
+		if (carData) {
+			// carDataStore.set(carData.entries[0].cars);
+			updateCarData(carData.entries[0].cars);
 
-			for (const entry of carData.Entries) {
-				carBuffer.pushTimed(entry.Cars, utcToLocalMs(entry.Utc));
+			for (const entry of carData.entries) {
+				carBuffer.pushTimed(entry.cars, utcToLocalMs(entry.utc));
 			}
 		}
 
-		if (positionZ) {
-			const position = inflate<Position>(positionZ);
-			// positionStore.set(position.Position[0].Entries);
-			updatePosition(position.Position[0].Entries);
+		if (position) {
+			// positionStore.set(position.position[0].entries);
+			updatePosition(position.position[0].entries);
 
-			for (const entry of position.Position) {
-				posBuffer.pushTimed(entry.Entries, utcToLocalMs(entry.Timestamp));
+			for (const entry of position.position) {
+				posBuffer.pushTimed(entry.entries, utcToLocalMs(entry.timestamp));
 			}
 		}
 	};
 
-	const handleUpdate = ({ carDataZ, positionZ, ...update }: MessageUpdate) => {
+	const handleUpdate = ({ timestamp, update: { carData, position, ...update } }: MessageUpdate) => {
+		const updateMs = timestamp ? utcToLocalMs(timestamp) : undefined;
+
 		Object.keys(buffers).forEach((key) => {
 			const data = update[key as keyof typeof update];
 			const buffer = buffers[key as keyof typeof buffers];
-			if (data) buffer.push(data);
+			if (data) buffer.push(data, updateMs);
 		});
 
-		if (carDataZ) {
-			const carData = inflate<CarData>(carDataZ);
-			for (const entry of carData.Entries) {
-				carBuffer.pushTimed(entry.Cars, utcToLocalMs(entry.Utc));
+		if (carData?.entries) {
+			for (const entry of carData.entries) {
+				if (!entry?.cars || !entry.utc) continue;
+				carBuffer.pushTimed(entry.cars as CarsData, utcToLocalMs(entry.utc));
 			}
 		}
 
-		if (positionZ) {
-			const position = inflate<Position>(positionZ);
-			for (const entry of position.Position) {
-				posBuffer.pushTimed(entry.Entries, utcToLocalMs(entry.Timestamp));
+		if (position?.position) {
+			for (const entry of position.position) {
+				if (!entry?.entries || !entry.timestamp) continue;
+				posBuffer.pushTimed(entry.entries as Positions, utcToLocalMs(entry.timestamp));
 			}
 		}
 	};
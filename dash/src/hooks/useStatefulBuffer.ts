@@ -6,7 +6,8 @@ import { useBuffer } from "@/hooks/useBuffer";
 
 import { RecursivePartial } from "@/types/message.type";
 
-export const useStatefulBuffer = <T>() => {
+// `topic` is the key of the buffered topic in the state, it picks how its updates are merged
+export const useStatefulBuffer = <T>(topic: string) => {
 	const currentRef = useRef<T | null>(null);
 	const buffer = useBuffer<T>();
 
@@ -15,9 +16,9 @@ export const useStatefulBuffer = <T>() => {
 		buffer.set(data);
 	};
 
-	const push = (update: RecursivePartial<T>) => {
-		currentRef.current = merge(currentRef.current ?? {}, update);
-		if (currentRef.current) buffer.push(currentRef.current);
+	const push = (update: RecursivePartial<T>, timestamp?: number) => {
+		currentRef.current = merge(currentRef.current ?? {}, update, [topic]);
+		if (currentRef.current) buffer.pushTimed(currentRef.current, timestamp ?? Date.now());
 	};
 
 	return {
@@ -2,31 +2,161 @@ const isObject = (obj: any): boolean => {
 	return obj !== null && typeof obj === "object" && !Array.isArray(obj);
 };
 
-export const merge = (base: any, update: any): any => {
+// the f1 feed retracts data with `{ _deleted: ["44"] }`, removing key "44" (or index 44 of a list)
+const DELETED = "_deleted";
+
+// how the values at a path are merged with an update, the same table the backend merges with
+// (`Strategies::feed` in crates/data/src/merge.rs), keep the two in sync
+type Strategy =
+	// objects merge key by key, a new array is appended to the old one, an object patches an array by index
+	| { type: "append" }
+	// the update replaces the value as is, for topics that always send everything
+	| { type: "replace" }
+	// a new array patches the old one item by item instead of being appended
+	| { type: "indexPatch" }
+	// array items are matched by the value of this field, matches are merged and the rest appended
+	| { type: "keyed"; field: string };
+
+// "TimingAppData", "timingAppData" and "timing_app_data" are all "timingappdata"
+const normalize = (key: string): string => key.replaceAll("_", "").toLowerCase();
+
+const rule = (path: string, strategy: Strategy): [string[], Strategy] => [
+	path.split("/").filter((key) => key !== "").map(normalize),
+	strategy,
+];
+
+const strategies: [string[], Strategy][] = [
+	// a full batch of samples every time
+	rule("carData", { type: "replace" }),
+	rule("position", { type: "replace" }),
+	// new entries are sent on their own, the lists only ever grow
+	rule("raceControlMessages/messages", { type: "append" }),
+	rule("sessionData/series", { type: "append" }),
+	rule("sessionData/statusSeries", { type: "append" }),
+	// every capture has its own recording, resent ones are the same capture
+	rule("teamRadio/captures", { type: "keyed", field: "path" }),
+	// fixed length lists, sent whole in the initial and patched by index after
+	rule("timingAppData/lines/*/stints", { type: "indexPatch" }),
+	rule("timingData/lines/*/sectors", { type: "indexPatch" }),
+	rule("timingData/lines/*/sectors/*/segments", { type: "indexPatch" }),
+	rule("timingStats/lines/*/bestSectors", { type: "indexPatch" }),
+	rule("topThree/lines", { type: "indexPatch" }),
+];
+
+// the first matching rule wins, everything without a rule is appended
+const strategyAt = (path: string[]): Strategy => {
+	const found = strategies.find(
+		([rule]) => rule.length === path.length && rule.every((key, i) => key === "*" || key === path[i]),
+	);
+
+	return found ? found[1] : { type: "append" };
+};
+
+const fieldValue = (item: any, field: string): any => {
+	if (!isObject(item)) return undefined;
+
+	const key = Object.keys(item).find((key) => normalize(key) === normalize(field));
+	return key !== undefined ? item[key] : undefined;
+};
+
+const deletedKeys = (update: any): string[] => {
+	const deleted = update[DELETED];
+	if (deleted === undefined || deleted === null) return [];
+	return (Array.isArray(deleted) ? deleted : [deleted]).map((key) => `${key}`);
+};
+
+const withoutDeleted = (value: any): any => {
+	if (Array.isArray(value)) return value.map(withoutDeleted);
+	if (!isObject(value)) return value;
+
+	const result: any = {};
+
+	for (const [key, item] of Object.entries(value)) {
+		if (key !== DELETED) result[key] = withoutDeleted(item);
+	}
+
+	return result;
+};
+
+/**
+ * merges an update into the state at `path`, the keys from the top of the state
+ * (e.g. `["timingData"]` for the timing data buffer), which picks the strategy of every value below it
+ */
+export const merge = (base: any, update: any, path: string[] = []): any => {
+	const strategy = strategyAt(path.map(normalize));
+	const child = (key: string) => [...path, key];
+
+	if (strategy.type === "replace") {
+		return withoutDeleted(update);
+	}
+
+	if (strategy.type === "indexPatch" && Array.isArray(base) && Array.isArray(update)) {
+		const result = [...base];
+
+		update.forEach((item, index) => {
+			result[index] = index < result.length ? merge(result[index], item, child(`${index}`)) : withoutDeleted(item);
+		});
+
+		return result;
+	}
+
+	if (strategy.type === "keyed" && Array.isArray(base) && Array.isArray(update)) {
+		const result = [...base];
+
+		for (const item of update) {
+			const key = fieldValue(item, strategy.field);
+			const index = key !== undefined ? result.findIndex((prev) => fieldValue(prev, strategy.field) === key) : -1;
+
+			if (index >= 0) {
+				result[index] = merge(result[index], item, child(`${index}`));
+			} else {
+				result.push(withoutDeleted(item));
+			}
+		}
+
+		return result;
+	}
+
 	if (isObject(base) && isObject(update)) {
 		const result = { ...base };
 
+		for (const key of deletedKeys(update)) {
+			delete result[key];
+		}
+
 		for (const [key, value] of Object.entries(update)) {
-			result[key] = merge(base[key] ?? null, value);
+			if (key === DELETED) continue;
+			result[key] = merge(result[key] ?? null, value, child(key));
 		}
 
 		return result;
 	}
 
 	if (Array.isArray(base) && Array.isArray(update)) {
-		return base.concat(update);
+		return base.concat(withoutDeleted(update));
 	}
 
 	if (Array.isArray(base) && isObject(update)) {
-		const result = [...base];
+		const deleted = deletedKeys(update)
+			.map((key) => parseInt(key))
+			.filter((index) => !isNaN(index));
+
+		const result = base.filter((_, index) => !deleted.includes(index));
 
 		for (const [key, value] of Object.entries(update)) {
+			if (key === DELETED) continue;
 			const index = parseInt(key);
-			result.splice(index, 1, merge(result[index], value));
+			if (isNaN(index)) continue;
+
+			if (index < result.length) {
+				result[index] = merge(result[index], value, child(key));
+			} else {
+				result.push(withoutDeleted(value));
+			}
 		}
 
-		return [...result];
+		return result;
 	}
 
-	return update;
+	return withoutDeleted(update);
 };
@@ -1,4 +1,4 @@
-import { type State } from "./state.type";
+import type { CarData, Position, State } from "./state.type";
 
 export type RecursivePartial<T> = {
 	[P in keyof T]?: T[P] extends (infer U)[]
@@ -9,10 +9,14 @@ export type RecursivePartial<T> = {
 };
 
 type FullState = State & {
-	carDataZ?: string;
-	positionZ?: string;
+	carData?: CarData;
+	position?: Position;
 };
 
-export type MessageUpdate = RecursivePartial<FullState>;
+export type MessageUpdate = {
+	// when f1 sent the update, utc iso string
+	timestamp?: string;
+	update: RecursivePartial<FullState>;
+};
 
 export type MessageInitial = FullState;
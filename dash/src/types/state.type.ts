@@ -301,12 +301,12 @@ export type ChampionshipTeam = {
 };
 
 export type Position = {
-	Position: PositionItem[];
+	position: PositionItem[];
 };
 
 export type PositionItem = {
-	Timestamp: string;
-	Entries: Positions;
+	timestamp: string;
+	entries: Positions;
 };
 
 export type Positions = {
@@ -315,25 +315,25 @@ export type Positions = {
 };
 
 export type PositionCar = {
-	Status: string;
-	X: number;
-	Y: number;
-	Z: number;
+	status: string;
+	x: number;
+	y: number;
+	z: number;
 };
 
 export type CarData = {
-	Entries: Entry[];
+	entries: Entry[];
 };
 
 export type Entry = {
-	Utc: string;
-	Cars: CarsData;
+	utc: string;
+	cars: CarsData;
 };
 
 export type CarsData = {
 	// this is what we have at state
 	[key: string]: {
-		Channels: CarDataChannels;
+		channels: CarDataChannels;
 	};
 };
 